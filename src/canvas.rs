@@ -0,0 +1,176 @@
+//! Backbuffer the sketch draws into, it survives between frames so drawings can accumulate.
+
+use simple_pixels::{rgb::RGBA8, Context};
+
+use crate::math::Vec2;
+
+/// What happens to the previous frame when a new one begins.
+#[derive(Debug, Copy, Clone)]
+pub enum Background {
+    /// Fill with the color, like calling `background()` every frame.
+    Clear(RGBA8),
+    /// Leave the previous frame untouched, drawings accumulate.
+    Keep,
+    /// Move every pixel `rate` (0 to 1) of the way toward the color, leaving trails behind moving shapes.
+    Fade { color: RGBA8, rate: f32 },
+}
+
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<RGBA8>,
+    pub background: Background,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Background) -> Self {
+        let black = RGBA8::new(0, 0, 0, 255);
+        Self {
+            width,
+            height,
+            pixels: vec![black; (width * height) as usize],
+            background,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[RGBA8] {
+        &self.pixels
+    }
+
+    /// Applies the background mode, call once before drawing a frame.
+    pub fn begin_frame(&mut self) {
+        match self.background {
+            Background::Clear(color) => self.clear(color),
+            Background::Keep => {}
+            Background::Fade { color, rate } => {
+                let rate = rate.clamp(0.0, 1.0);
+                for pixel in &mut self.pixels {
+                    *pixel = RGBA8::new(
+                        fade_channel(pixel.r, color.r, rate),
+                        fade_channel(pixel.g, color.g, rate),
+                        fade_channel(pixel.b, color.b, rate),
+                        fade_channel(pixel.a, color.a, rate),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self, color: RGBA8) {
+        self.pixels.fill(color);
+    }
+
+    /// Pixels outside of the canvas are ignored.
+    pub fn draw_pixel(&mut self, x: i32, y: i32, color: RGBA8) {
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.pixels[y as usize * self.width as usize + x as usize] = color;
+        }
+    }
+
+    pub fn draw_points(&mut self, points: &[Vec2], color: RGBA8) {
+        for point in points {
+            self.draw_pixel(point.x as i32, point.y as i32, color);
+        }
+    }
+
+    /// Copies a `width` x `height` block of pixels, the parts outside of the canvas are skipped.
+    pub fn draw_pixels(&mut self, x: i32, y: i32, width: u32, height: u32, pixels: &[RGBA8]) {
+        for iy in 0..height as i32 {
+            for ix in 0..width as i32 {
+                let color = pixels[(iy * width as i32 + ix) as usize];
+                self.draw_pixel(x + ix, y + iy, color);
+            }
+        }
+    }
+
+    /// Copies the canvas to the screen.
+    pub fn present(&self, ctx: &mut Context) {
+        ctx.draw_screen(&self.pixels);
+    }
+}
+
+fn fade_channel(from: u8, to: u8, rate: f32) -> u8 {
+    if rate <= 0.0 || from == to {
+        return from;
+    }
+    let delta = (f32::from(to) - f32::from(from)) * rate;
+    // Always move at least one step, otherwise rounding leaves ghosts that never fade out.
+    let step = if delta.abs() < 1.0 {
+        delta.signum()
+    } else {
+        delta.round()
+    };
+    (f32::from(from) + step).clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: RGBA8 = RGBA8::new(255, 255, 255, 255);
+    const BLACK: RGBA8 = RGBA8::new(0, 0, 0, 255);
+
+    #[test]
+    fn clear_and_keep() {
+        let mut canvas = Canvas::new(2, 2, Background::Keep);
+        canvas.draw_pixel(1, 0, WHITE);
+        canvas.begin_frame();
+        assert_eq!(canvas.pixels(), [BLACK, WHITE, BLACK, BLACK]);
+        canvas.background = Background::Clear(BLACK);
+        canvas.begin_frame();
+        assert_eq!(canvas.pixels(), [BLACK; 4]);
+    }
+
+    #[test]
+    fn fade_reaches_the_color() {
+        let mut canvas = Canvas::new(
+            1,
+            1,
+            Background::Fade {
+                color: BLACK,
+                rate: 0.1,
+            },
+        );
+        canvas.draw_pixel(0, 0, WHITE);
+        canvas.begin_frame();
+        let once = canvas.pixels()[0];
+        assert!(once.r < 255 && once.r > 200, "{once:?}");
+        // Small steps still move, so no ghost is left behind.
+        for _ in 0..100 {
+            canvas.begin_frame();
+        }
+        assert_eq!(canvas.pixels()[0], BLACK);
+    }
+
+    #[test]
+    fn zero_rate_keeps_the_frame() {
+        let mut canvas = Canvas::new(
+            1,
+            1,
+            Background::Fade {
+                color: BLACK,
+                rate: 0.0,
+            },
+        );
+        canvas.draw_pixel(0, 0, WHITE);
+        canvas.begin_frame();
+        assert_eq!(canvas.pixels()[0], WHITE);
+    }
+
+    #[test]
+    fn drawing_outside_is_ignored() {
+        let mut canvas = Canvas::new(2, 2, Background::Keep);
+        canvas.draw_pixel(-1, 0, WHITE);
+        canvas.draw_pixel(0, 2, WHITE);
+        canvas.draw_pixels(1, 1, 2, 2, &[WHITE; 4]);
+        assert_eq!(canvas.pixels(), [BLACK, BLACK, BLACK, WHITE]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Saving the contents of the screen to disk.
+
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::canvas::Canvas;
+use crate::geometry::Size;
+use crate::math::Vec2;
+use crate::ppt::save_sprite;
+use crate::sprite::Sprite;
+
+/// Writes the canvas to `path` in ppt format.
+pub fn save_frame<P: AsRef<Path>>(canvas: &Canvas, path: P) -> Result<(), io::Error> {
+    let sprite = Sprite::new(
+        Vec2::new(0.0, 0.0),
+        Size::new(canvas.width(), canvas.height()),
+        canvas.pixels().to_vec(),
+    );
+    save_sprite(path, &sprite)
+}
+
+/// Builds the path for a saved frame: the file name gets `{sketch}-{seed}-` prepended,
+/// every run of `#` is replaced with the frame number padded to the run's length
+/// and `%t` is replaced with the seconds since the unix epoch.
+///
+/// `frame_path("out/####.ppm", "game", 42, 7)` -> `out/game-42-0007.ppm`
+pub fn frame_path(pattern: &str, sketch: &str, seed: u64, frame: u64) -> PathBuf {
+    let pattern = Path::new(pattern);
+    let file_name = pattern
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let file_name = format!("{sketch}-{seed}-{}", expand_pattern(&file_name, frame));
+    match pattern.parent() {
+        Some(parent) => parent.join(file_name),
+        None => PathBuf::from(file_name),
+    }
+}
+
+fn expand_pattern(pattern: &str, frame: u64) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let pattern = pattern.replace("%t", &timestamp.to_string());
+
+    let mut expanded = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            let mut width = 1;
+            while chars.next_if_eq(&'#').is_some() {
+                width += 1;
+            }
+            let _ = write!(expanded, "{frame:0width$}");
+        } else {
+            expanded.push(c);
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_pixels::rgb::RGBA8;
+
+    use super::*;
+    use crate::canvas::Background;
+    use crate::ppt::load_sprite;
+
+    #[test]
+    fn frame_path_pads_runs_of_hashes() {
+        assert_eq!(
+            frame_path("out/####.ppm", "game", 42, 7),
+            Path::new("out/game-42-0007.ppm")
+        );
+        assert_eq!(
+            frame_path("#-##.ppm", "game", 1, 5),
+            Path::new("game-1-5-05.ppm")
+        );
+        // A frame number longer than the run isn't cut.
+        assert_eq!(
+            frame_path("##.ppm", "game", 1, 1234),
+            Path::new("game-1-1234.ppm")
+        );
+    }
+
+    #[test]
+    fn frame_path_replaces_the_timestamp() {
+        let path = frame_path("%t.ppm", "game", 1, 0);
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let stamp = name
+            .strip_prefix("game-1-")
+            .and_then(|name| name.strip_suffix(".ppm"))
+            .unwrap();
+        assert!(!stamp.is_empty() && stamp.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn saved_frame_loads_back() {
+        let red = RGBA8::new(255, 0, 0, 255);
+        let mut canvas = Canvas::new(3, 2, Background::Keep);
+        canvas.draw_pixel(2, 1, red);
+        let path = std::env::temp_dir().join(format!("capture-test-{}.ppm", std::process::id()));
+        save_frame(&canvas, &path).unwrap();
+        let sprite = load_sprite(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((sprite.size.width, sprite.size.height), (3, 2));
+        assert_eq!(sprite.pixels, canvas.pixels());
+    }
+}
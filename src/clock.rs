@@ -2,7 +2,6 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 /// Keeps a consistent framerate.
-
 pub struct Clock {
     creation_time: Instant,
     past: [Duration; 2],
@@ -30,7 +29,7 @@ impl Clock {
         self.lifetime = now.duration_since(self.creation_time);
         self.past[1] = self.past[0];
         self.past[0] = time_delta;
-        if self.cycles % 15 == 0 {
+        if self.cycles.is_multiple_of(15) {
             let fps = 1.0 / time_delta.as_secs_f32();
             println!(
                 "playtime: {:.2}, fps: {fps:.2}",
@@ -56,4 +55,9 @@ impl Clock {
     pub fn now(&self) -> f32 {
         self.lifetime.as_secs_f32()
     }
+
+    /// Number of frames since creation.
+    pub fn frame(&self) -> u64 {
+        self.cycles
+    }
 }
@@ -3,8 +3,8 @@ pub fn lerp(start: f32, end: f32, t: f32) -> f32 {
 }
 
 pub fn lerp_u8(start: u8, end: u8, t: f32) -> u8 {
-    let s = start as f32;
-    let e = end as f32;
+    let s = f32::from(start);
+    let e = f32::from(end);
     (s.mul_add(1.0 - t, e * t) % 255.0) as u8
 }
 
@@ -0,0 +1,262 @@
+//! Easing functions, they remap animation progress in [0, 1] to change its pacing.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Shape of an easing curve, the direction is picked with `Easing::In`, `Out` or `InOut`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Curve {
+    Quad,
+    Cubic,
+    Quart,
+    Quint,
+    Sine,
+    Expo,
+    Circ,
+    /// Pulls back a little before moving.
+    Back,
+    /// Overshoots and wobbles like a spring.
+    Elastic,
+    Bounce,
+}
+
+impl Curve {
+    /// The "in" form, starts slow and ends fast.
+    #[allow(clippy::suboptimal_flops)]
+    fn ease_in(self, t: f32) -> f32 {
+        match self {
+            Self::Quad => t * t,
+            Self::Cubic => t * t * t,
+            Self::Quart => t * t * t * t,
+            Self::Quint => t * t * t * t * t,
+            Self::Sine => 1.0 - (t * FRAC_PI_2).cos(),
+            Self::Expo => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2.0_f32.powf(10.0 * t - 10.0)
+                }
+            }
+            Self::Circ => 1.0 - (1.0 - t * t).max(0.0).sqrt(),
+            Self::Back => {
+                let overshoot = 1.70158;
+                t * t * ((overshoot + 1.0) * t - overshoot)
+            }
+            Self::Elastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    -(2.0_f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * TAU / 3.0).sin()
+                }
+            }
+            Self::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+#[allow(clippy::suboptimal_flops)]
+fn bounce_out(t: f32) -> f32 {
+    let (n, d) = (7.5625, 2.75);
+    if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - 1.5 / d;
+        n * t * t + 0.75
+    } else if t < 2.5 / d {
+        let t = t - 2.25 / d;
+        n * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d;
+        n * t * t + 0.984_375
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    In(Curve),
+    Out(Curve),
+    InOut(Curve),
+    CubicBezier(CubicBezier),
+}
+
+impl Easing {
+    /// Eased progress, `t` is clamped to [0, 1]. Back and elastic curves leave [0, 1] in between.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::In(curve) => curve.ease_in(t),
+            Self::Out(curve) => 1.0 - curve.ease_in(1.0 - t),
+            Self::InOut(curve) => {
+                if t < 0.5 {
+                    curve.ease_in(t * 2.0) / 2.0
+                } else {
+                    1.0 - curve.ease_in(2.0 - t * 2.0) / 2.0
+                }
+            }
+            Self::CubicBezier(bezier) => bezier.apply(t),
+        }
+    }
+}
+
+/// Timing curve from (0, 0) to (1, 1) with two control points, like CSS `cubic-bezier()`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CubicBezier {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl CubicBezier {
+    /// `x1` and `x2` are clamped to [0, 1] so the curve is a function of time.
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self {
+            x1: x1.clamp(0.0, 1.0),
+            y1,
+            x2: x2.clamp(0.0, 1.0),
+            y2,
+        }
+    }
+
+    pub fn apply(self, x: f32) -> f32 {
+        let s = self.solve_x(x);
+        bezier_component(self.y1, self.y2, s)
+    }
+
+    /// Curve parameter at which the curve reaches `x`: Newton's method, bisection if it stalls.
+    fn solve_x(self, x: f32) -> f32 {
+        let mut s = x;
+        for _ in 0..8 {
+            let error = bezier_component(self.x1, self.x2, s) - x;
+            if error.abs() < 1e-6 {
+                return s;
+            }
+            let slope = bezier_slope(self.x1, self.x2, s);
+            if slope.abs() < 1e-6 {
+                break;
+            }
+            s -= error / slope;
+        }
+
+        let (mut low, mut high) = (0.0, 1.0);
+        s = x;
+        for _ in 0..32 {
+            let value = bezier_component(self.x1, self.x2, s);
+            if (value - x).abs() < 1e-6 {
+                break;
+            }
+            if value < x {
+                low = s;
+            } else {
+                high = s;
+            }
+            s = f32::midpoint(low, high);
+        }
+        s
+    }
+}
+
+/// One coordinate of a cubic bezier with end points 0 and 1.
+#[allow(clippy::suboptimal_flops)]
+fn bezier_component(p1: f32, p2: f32, s: f32) -> f32 {
+    let inverse = 1.0 - s;
+    3.0 * inverse * inverse * s * p1 + 3.0 * inverse * s * s * p2 + s * s * s
+}
+
+#[allow(clippy::suboptimal_flops)]
+fn bezier_slope(p1: f32, p2: f32, s: f32) -> f32 {
+    let inverse = 1.0 - s;
+    3.0 * inverse * inverse * p1 + 6.0 * inverse * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 10] = [
+        Curve::Quad,
+        Curve::Cubic,
+        Curve::Quart,
+        Curve::Quint,
+        Curve::Sine,
+        Curve::Expo,
+        Curve::Circ,
+        Curve::Back,
+        Curve::Elastic,
+        Curve::Bounce,
+    ];
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn every_easing_starts_at_0_and_ends_at_1() {
+        for curve in CURVES {
+            for easing in [Easing::In(curve), Easing::Out(curve), Easing::InOut(curve)] {
+                assert!(close(easing.apply(0.0), 0.0), "{easing:?}");
+                assert!(close(easing.apply(1.0), 1.0), "{easing:?}");
+                // Out of range progress is clamped.
+                assert!(close(easing.apply(-1.0), 0.0), "{easing:?}");
+                assert!(close(easing.apply(2.0), 1.0), "{easing:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn in_out_is_symmetric() {
+        for curve in CURVES {
+            let easing = Easing::InOut(curve);
+            assert!(close(easing.apply(0.5), 0.5), "{curve:?}");
+            for t in [0.1, 0.25, 0.4] {
+                let mirrored = 1.0 - easing.apply(1.0 - t);
+                assert!(close(easing.apply(t), mirrored), "{curve:?} {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn in_is_slow_and_out_is_fast() {
+        assert!(close(Easing::In(Curve::Quad).apply(0.5), 0.25));
+        assert!(close(Easing::Out(Curve::Quad).apply(0.5), 0.75));
+        assert!(Easing::In(Curve::Back).apply(0.2) < 0.0);
+        assert!(Easing::Out(Curve::Back).apply(0.8) > 1.0);
+    }
+
+    #[test]
+    fn cubic_bezier_matches_known_points() {
+        // Control points on the diagonal make the curve linear.
+        let linear = CubicBezier::new(0.25, 0.25, 0.75, 0.75);
+        for t in [0.0, 0.1, 0.3, 0.5, 0.9, 1.0] {
+            assert!(close(linear.apply(t), t), "{t}");
+        }
+        // CSS `ease-in-out` is symmetric around the middle.
+        let ease_in_out = CubicBezier::new(0.42, 0.0, 0.58, 1.0);
+        assert!(close(ease_in_out.apply(0.5), 0.5));
+        assert!(close(ease_in_out.apply(0.2), 1.0 - ease_in_out.apply(0.8)));
+        assert!(ease_in_out.apply(0.2) < 0.2);
+    }
+
+    #[test]
+    fn cubic_bezier_solves_steep_curves() {
+        // Flat start in x, Newton's method stalls there and bisection takes over.
+        let bezier = CubicBezier::new(1.0, 0.0, 1.0, 1.0);
+        let mut previous = 0.0;
+        for step in 0..=20 {
+            let x = step as f32 / 20.0;
+            let s = bezier.solve_x(x);
+            assert!((bezier_component(1.0, 1.0, s) - x).abs() < 1e-4, "{x}");
+            let y = bezier.apply(x);
+            assert!(y >= previous - 1e-6, "{x}");
+            previous = y;
+        }
+    }
+
+    #[test]
+    fn control_points_are_clamped_in_x() {
+        let bezier = CubicBezier::new(-1.0, 2.0, 3.0, -1.0);
+        assert_eq!((bezier.x1, bezier.x2), (0.0, 1.0));
+    }
+}
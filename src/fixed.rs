@@ -0,0 +1,538 @@
+//! Q16.16 fixed-point numbers for simulations that are recorded and replayed.
+//! Everything is integer arithmetic, so results are bit-identical on every machine and
+//! optimisation level. Overflow wraps, the same way in debug and release builds.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+
+use crate::math::Vec2;
+
+const FRACTION_BITS: u32 = 16;
+/// Entries in a quarter turn of the sine table.
+const QUARTER: usize = 1024;
+/// The table holds sines with 30 fraction bits, rounded to 16 after interpolating.
+const TABLE_BITS: u32 = 30;
+/// Computed with integers at compile time, so it doesn't depend on the platform's `sin`.
+static SINE: [i32; QUARTER + 1] = sine_table();
+
+/// Signed number with 16 integer and 16 fraction bits, the range is about ±32768
+/// with a step of 1/65536.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << FRACTION_BITS);
+    pub const HALF: Self = Self(1 << (FRACTION_BITS - 1));
+    pub const MIN: Self = Self(i32::MIN);
+    pub const MAX: Self = Self(i32::MAX);
+    pub const PI: Self = Self(205_887);
+    pub const TAU: Self = Self(411_775);
+    pub const FRAC_PI_2: Self = Self(102_944);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Self(value.wrapping_shl(FRACTION_BITS))
+    }
+
+    /// Nearest fixed-point value, saturating outside of the range.
+    pub fn from_f32(value: f32) -> Self {
+        Self((value * Self::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::ONE.0 as f32
+    }
+
+    /// Integer part, rounded down.
+    pub const fn to_int(self) -> i32 {
+        self.0 >> FRACTION_BITS
+    }
+
+    pub const fn floor(self) -> Self {
+        Self(self.0 & !(Self::ONE.0 - 1))
+    }
+
+    pub const fn ceil(self) -> Self {
+        Self(self.0.wrapping_add(Self::ONE.0 - 1)).floor()
+    }
+
+    /// Rounds halves up.
+    pub const fn round(self) -> Self {
+        Self(self.0.wrapping_add(Self::HALF.0)).floor()
+    }
+
+    /// Part after the decimal point, always in [0, 1).
+    pub const fn fract(self) -> Self {
+        Self(self.0 & (Self::ONE.0 - 1))
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.wrapping_abs())
+    }
+
+    pub const fn signum(self) -> Self {
+        Self::from_int(self.0.signum())
+    }
+
+    pub fn lerp(self, other: Self, t: Self) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Rounded down square root.
+    pub fn sqrt(self) -> Self {
+        assert!(
+            self.0 >= 0,
+            "Can't take the square root of a negative number"
+        );
+        let widened = (self.0 as u64) << FRACTION_BITS;
+        Self(widened.isqrt() as i32)
+    }
+
+    /// Sine of an angle in radians, interpolated from the lookup table.
+    pub fn sin(self) -> Self {
+        sine_at(self.table_position())
+    }
+
+    pub fn cos(self) -> Self {
+        sine_at(self.table_position() + ((QUARTER as i64) << FRACTION_BITS))
+    }
+
+    /// Position of the angle in a full turn of table entries, with 16 fraction bits.
+    fn table_position(self) -> i64 {
+        let turn = i64::from(Self::TAU.0);
+        let angle = i64::from(self.0).rem_euclid(turn);
+        (angle << FRACTION_BITS) * (4 * QUARTER as i64) / turn
+    }
+}
+
+fn sine_at(position: i64) -> Fixed {
+    let index = (position >> FRACTION_BITS) as usize % (4 * QUARTER);
+    let fraction = position & ((1 << FRACTION_BITS) - 1);
+    let (quadrant, offset) = (index / QUARTER, index % QUARTER);
+    let interpolate = |from: usize, to: usize| {
+        let (from, to) = (i64::from(SINE[from]), i64::from(SINE[to]));
+        from + (((to - from) * fraction) >> FRACTION_BITS)
+    };
+    // The table covers the first quarter turn, the other three mirror it.
+    let value = match quadrant {
+        0 => interpolate(offset, offset + 1),
+        1 => interpolate(QUARTER - offset, QUARTER - offset - 1),
+        2 => -interpolate(offset, offset + 1),
+        _ => -interpolate(QUARTER - offset, QUARTER - offset - 1),
+    };
+    let shift = TABLE_BITS - FRACTION_BITS;
+    Fixed(((value + (1 << (shift - 1))) >> shift) as i32)
+}
+
+/// Sines over a quarter turn from a Taylor series in 40 fraction bit integers.
+const fn sine_table() -> [i32; QUARTER + 1] {
+    const BITS: u32 = 40;
+    /// Pi with 40 fraction bits.
+    const PI: i128 = 3_454_217_652_358;
+    let mut table = [0; QUARTER + 1];
+    let mut i = 0;
+    while i <= QUARTER {
+        let x = PI * i as i128 / (2 * QUARTER as i128);
+        let x_squared = (x * x) >> BITS;
+        let mut term = x;
+        let mut sum = x;
+        let mut k = 1;
+        while k < 12 {
+            term = -((term * x_squared) >> BITS) / ((2 * k) * (2 * k + 1));
+            sum += term;
+            k += 1;
+        }
+        let shift = BITS - TABLE_BITS;
+        table[i] = ((sum + (1 << (shift - 1))) >> shift) as i32;
+        i += 1;
+    }
+    table
+}
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Self::from_int(value)
+    }
+}
+
+impl From<Fixed> for f32 {
+    fn from(value: Fixed) -> Self {
+        value.to_f32()
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f32())
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    /// Rounded to the nearest step.
+    fn mul(self, rhs: Self) -> Self::Output {
+        let product = i64::from(self.0) * i64::from(rhs.0);
+        Self(((product + (1 << (FRACTION_BITS - 1))) >> FRACTION_BITS) as i32)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    /// Rounded towards zero, panics when dividing by zero like integers do.
+    fn div(self, rhs: Self) -> Self::Output {
+        Self(((i64::from(self.0) << FRACTION_BITS) / i64::from(rhs.0)) as i32)
+    }
+}
+
+impl Rem for Fixed {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        Self(self.0.wrapping_rem(rhs.0))
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Fixed {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+/// `Vec2` made of `Fixed` components.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FixedVec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl FixedVec2 {
+    pub const ZERO: Self = Self::new(Fixed::ZERO, Fixed::ZERO);
+
+    pub const fn new(x: Fixed, y: Fixed) -> Self {
+        Self { x, y }
+    }
+
+    pub fn from_vec2(vec: Vec2) -> Self {
+        Self::new(Fixed::from_f32(vec.x), Fixed::from_f32(vec.y))
+    }
+
+    pub fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+
+    pub fn from_angle(angle: Fixed) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    pub fn dot(self, other: Self) -> Fixed {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn cross(self, other: Self) -> Fixed {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Wraps for vectors longer than about 181.
+    pub fn length_squared(self) -> Fixed {
+        self.dot(self)
+    }
+
+    /// Squared in 64 bits, so it works over the whole range.
+    pub fn len(self) -> Fixed {
+        let (x, y) = (
+            u64::from(self.x.0.unsigned_abs()),
+            u64::from(self.y.0.unsigned_abs()),
+        );
+        let squared = x * x + y * y;
+        Fixed(squared.isqrt() as i32)
+    }
+
+    pub fn distance(self, other: Self) -> Fixed {
+        (self - other).len()
+    }
+
+    /// Zero vectors stay zero.
+    pub fn normalize(self) -> Self {
+        let len = self.len();
+        if len == Fixed::ZERO {
+            self
+        } else {
+            self / len
+        }
+    }
+
+    pub fn rotate(self, angle: Fixed) -> Self {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub fn lerp(self, other: Self, t: Fixed) -> Self {
+        Self::new(self.x.lerp(other.x, t), self.y.lerp(other.y, t))
+    }
+}
+
+impl From<Vec2> for FixedVec2 {
+    fn from(vec: Vec2) -> Self {
+        Self::from_vec2(vec)
+    }
+}
+
+impl From<FixedVec2> for Vec2 {
+    fn from(vec: FixedVec2) -> Self {
+        vec.to_vec2()
+    }
+}
+
+impl Add for FixedVec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for FixedVec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<Fixed> for FixedVec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Fixed) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<Fixed> for FixedVec2 {
+    type Output = Self;
+
+    fn div(self, rhs: Fixed) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Neg for FixedVec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for FixedVec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for FixedVec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<Fixed> for FixedVec2 {
+    fn mul_assign(&mut self, rhs: Fixed) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign<Fixed> for FixedVec2 {
+    fn div_assign(&mut self, rhs: Fixed) {
+        *self = *self / rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(value: f32) -> Fixed {
+        Fixed::from_f32(value)
+    }
+
+    /// Steps of 1/65536 between `value` and `expected`.
+    fn steps(value: Fixed, expected: f64) -> f64 {
+        (f64::from(value.to_bits()) / 65536.0 - expected).abs() * 65536.0
+    }
+
+    #[test]
+    fn constants_are_the_nearest_steps() {
+        use std::f64::consts::{FRAC_PI_2, PI, TAU};
+        assert!(steps(Fixed::PI, PI) <= 0.5);
+        assert!(steps(Fixed::TAU, TAU) <= 0.5);
+        assert!(steps(Fixed::FRAC_PI_2, FRAC_PI_2) <= 0.5);
+        assert_eq!(Fixed::ONE.to_f32().to_bits(), 1.0_f32.to_bits());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Fixed::from_int(-3).to_int(), -3);
+        assert_eq!(fixed(1.5).to_bits(), 3 << 15);
+        assert_eq!(fixed(-0.25).to_f32().to_bits(), (-0.25_f32).to_bits());
+        // Saturates instead of wrapping.
+        assert_eq!(fixed(1e9), Fixed::MAX);
+        assert_eq!(fixed(-1e9), Fixed::MIN);
+        assert_eq!(Fixed::from(7), Fixed::from_int(7));
+    }
+
+    #[test]
+    fn rounding_negative_numbers() {
+        let value = fixed(-2.25);
+        assert_eq!(value.to_int(), -3);
+        assert_eq!(value.floor(), Fixed::from_int(-3));
+        assert_eq!(value.ceil(), Fixed::from_int(-2));
+        assert_eq!(value.round(), Fixed::from_int(-2));
+        assert_eq!(value.fract(), fixed(0.75));
+        assert_eq!(fixed(-2.5).round(), Fixed::from_int(-2));
+        assert_eq!(value.abs(), fixed(2.25));
+        assert_eq!(value.signum(), Fixed::from_int(-1));
+        assert_eq!(Fixed::ZERO.signum(), Fixed::ZERO);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(fixed(1.5) * fixed(-2.0), fixed(-3.0));
+        // The smallest step squared rounds to the nearest step, which is zero.
+        assert_eq!(Fixed::from_bits(1) * Fixed::from_bits(1), Fixed::ZERO);
+        assert_eq!(Fixed::from_bits(3) * Fixed::HALF, Fixed::from_bits(2));
+        assert_eq!(Fixed::ONE / Fixed::from_int(3), Fixed::from_bits(21_845));
+        assert_eq!(-Fixed::ONE / Fixed::from_int(3), Fixed::from_bits(-21_845));
+        assert_eq!(fixed(5.5) % Fixed::from_int(2), fixed(1.5));
+        assert_eq!(fixed(2.0).lerp(fixed(4.0), fixed(0.25)), fixed(2.5));
+
+        let mut value = Fixed::ONE;
+        value += Fixed::ONE;
+        value *= fixed(1.5);
+        value -= Fixed::HALF;
+        value /= Fixed::from_int(5);
+        assert_eq!(value, Fixed::HALF);
+    }
+
+    #[test]
+    fn overflow_wraps() {
+        assert_eq!(Fixed::MAX + Fixed::from_bits(1), Fixed::MIN);
+        assert_eq!(Fixed::MIN - Fixed::from_bits(1), Fixed::MAX);
+        assert_eq!(-Fixed::MIN, Fixed::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "divide by zero")]
+    fn dividing_by_zero_panics() {
+        let _ = Fixed::ONE / Fixed::ZERO;
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        assert_eq!(Fixed::from_int(9).sqrt(), Fixed::from_int(3));
+        assert_eq!(fixed(0.25).sqrt(), Fixed::HALF);
+        for bits in [1, 2, 12_345, 1 << 20, i32::MAX] {
+            let root = Fixed::from_bits(bits).sqrt();
+            let exact = (f64::from(bits) / 65536.0).sqrt();
+            assert!(f64::from(root.to_bits()) / 65536.0 <= exact, "{bits}");
+            assert!(steps(root, exact) < 1.0, "{bits}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "negative")]
+    fn sqrt_of_negative_panics() {
+        let _ = fixed(-1.0).sqrt();
+    }
+
+    #[test]
+    fn sine_and_cosine_match_f64() {
+        for step in -2000..=2000 {
+            let angle = Fixed::from_bits(step * 257);
+            let radians = f64::from(angle.to_bits()) / 65536.0;
+            assert!(steps(angle.sin(), radians.sin()) <= 2.0, "{radians}");
+            assert!(steps(angle.cos(), radians.cos()) <= 2.0, "{radians}");
+        }
+        assert_eq!(Fixed::ZERO.sin(), Fixed::ZERO);
+        assert_eq!(Fixed::ZERO.cos(), Fixed::ONE);
+        assert_eq!(Fixed::FRAC_PI_2.sin(), Fixed::ONE);
+    }
+
+    #[test]
+    fn vectors() {
+        let v = FixedVec2::new(Fixed::from_int(3), Fixed::from_int(4));
+        assert_eq!(v.len(), Fixed::from_int(5));
+        assert_eq!(v.length_squared(), Fixed::from_int(25));
+        // Division rounds towards zero, one step below the nearest 0.6 and 0.8.
+        assert_eq!(
+            v.normalize(),
+            FixedVec2::new(Fixed::from_bits(39_321), Fixed::from_bits(52_428))
+        );
+        assert_eq!(FixedVec2::ZERO.normalize(), FixedVec2::ZERO);
+        assert_eq!(v.dot(v), Fixed::from_int(25));
+        assert_eq!(v.cross(v), Fixed::ZERO);
+        assert_eq!(v.distance(FixedVec2::ZERO), Fixed::from_int(5));
+
+        // Far beyond where the squared length wraps.
+        let far = FixedVec2::new(Fixed::from_int(20_000), Fixed::from_int(-20_000));
+        assert!(steps(far.len(), 20_000.0 * std::f64::consts::SQRT_2) < 1.0);
+
+        let turned = v.rotate(Fixed::FRAC_PI_2);
+        assert!(
+            turned.distance(FixedVec2::new(Fixed::from_int(-4), Fixed::from_int(3))) < fixed(0.001)
+        );
+        let round_trip = FixedVec2::from_vec2(Vec2::new(1.25, -7.5)).to_vec2();
+        assert_eq!(round_trip, Vec2::new(1.25, -7.5));
+    }
+}
@@ -1,6 +1,20 @@
 use crate::common::lerp;
 use crate::graphics::line;
-use crate::math::{diagonal_distance, point_is_in_triangle, Vec2};
+use crate::math::{diagonal_distance, point_is_in_triangle, Affine2, Vec2};
+use polygon::{FillRule, Polygon};
+use stroke::Stroke;
+
+pub mod bezier;
+pub mod boolean;
+pub mod collision;
+pub mod delaunay;
+pub mod ellipse;
+pub mod hull;
+pub mod intersect;
+pub mod polygon;
+pub mod spline;
+pub mod stroke;
+pub mod triangulate;
 
 /// Module for analytical forms of shapes
 
@@ -33,7 +47,7 @@ impl Rect {
         Self { origin, size }
     }
 
-    /// top_left, top_right, bottom_right, bottom_left
+    /// `top_left`, `top_right`, `bottom_right`, `bottom_left`
     pub fn corners(&self) -> (Vec2, Vec2, Vec2, Vec2) {
         let bottom_right = self.bottom_right();
         let top_right = Vec2::new(bottom_right.x, self.origin.y);
@@ -41,6 +55,18 @@ impl Rect {
         (self.origin, top_right, bottom_right, bottom_left)
     }
 
+    /// Transformed corners in the same order as `corners`, the result is only axis aligned
+    /// if the transform has no rotation.
+    pub fn transform(&self, transform: &Affine2) -> (Vec2, Vec2, Vec2, Vec2) {
+        let (a, b, c, d) = self.corners();
+        (
+            transform.transform_point(a),
+            transform.transform_point(b),
+            transform.transform_point(c),
+            transform.transform_point(d),
+        )
+    }
+
     pub fn bottom_right(&self) -> Vec2 {
         self.origin + Vec2::new(self.size.width as f32, self.size.height as f32)
     }
@@ -69,6 +95,12 @@ impl Rect {
         rectangle.extend(line(d, a));
         rectangle
     }
+
+    /// Outline of any width, `empty` is always 1 px.
+    pub fn stroked(&self, stroke: &Stroke) -> Vec<Vec2> {
+        let corners: [Vec2; 4] = self.corners().into();
+        stroke.solid(&corners, true)
+    }
 }
 
 #[derive(Debug)]
@@ -152,14 +184,20 @@ impl Line {
     }
 
     pub fn project(&self, point: Vec2) -> Vec2 {
-        let offset = self.a;
-        let point = offset - point;
-        let plane = offset - self.b;
-        offset + (plane * point.dot(plane) * plane.length_squared().recip())
+        let point = point - self.a;
+        let plane = self.b - self.a;
+        self.a + plane * (point.dot(plane) / plane.length_squared())
+    }
+
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::new(
+            transform.transform_point(self.a),
+            transform.transform_point(self.b),
+        )
     }
 
     pub fn solid(&self) -> Vec<Vec2> {
-        let Line { a, b } = *self;
+        let Self { a, b } = *self;
         let diagonal_distance = diagonal_distance(a, b);
         let mut points: Vec<Vec2> = Vec::with_capacity(diagonal_distance as usize);
         for i in 0..diagonal_distance as usize {
@@ -176,7 +214,7 @@ impl Line {
     }
 
     pub fn dotted(&self, step: f32) -> Vec<Vec2> {
-        let Line { a, b } = *self;
+        let Self { a, b } = *self;
         let diagonal_distance = diagonal_distance(a, b);
         let mut points: Vec<Vec2> = Vec::with_capacity(diagonal_distance as usize);
         for i in 0..(diagonal_distance / step) as usize {
@@ -191,6 +229,11 @@ impl Line {
         }
         points
     }
+
+    /// Line of any width, `solid` is always 1 px.
+    pub fn stroked(&self, stroke: &Stroke) -> Vec<Vec2> {
+        stroke.solid(&[self.a, self.b], false)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -210,21 +253,112 @@ impl Triangle {
         }
     }
 
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::new(
+            transform.transform_point(self.a),
+            transform.transform_point(self.b),
+            transform.transform_point(self.c),
+        )
+    }
+
     pub fn solid_color(&self) -> Vec<Vec2> {
-        let Triangle { a, b, c } = *self;
-        let rect_points = Rect::bounding(&[a, b, c])
+        let Self { a, b, c } = *self;
+        Rect::bounding(&[a, b, c])
             .solid_color()
             .into_iter()
             .filter(|point| point_is_in_triangle(*point, self))
-            .collect();
-        rect_points
+            .collect()
     }
 
     pub fn empty(&self) -> Vec<Vec2> {
-        let Triangle { a, b, c } = *self;
+        let Self { a, b, c } = *self;
         let mut triangle = line(a, b);
         triangle.extend(line(b, c));
         triangle.extend(line(a, c));
         triangle
     }
+
+    /// Outline of any width, `empty` is always 1 px.
+    pub fn stroked(&self, stroke: &Stroke) -> Vec<Vec2> {
+        let Self { a, b, c } = *self;
+        stroke.solid(&[a, b, c], true)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    pub fn bounding(&self) -> Rect {
+        let offset = Vec2::new(self.radius, self.radius);
+        Rect::bounding(&[self.center - offset, self.center + offset])
+    }
+}
+
+/// Rectangle turned by `angle` around its center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrientedRect {
+    pub center: Vec2,
+    /// Half of the width and height, before turning.
+    pub half_extents: Vec2,
+    pub angle: f32,
+}
+
+impl OrientedRect {
+    pub fn new(center: Vec2, half_extents: Vec2, angle: f32) -> Self {
+        Self {
+            center,
+            half_extents,
+            angle,
+        }
+    }
+
+    /// Unit vectors along the width and the height.
+    pub fn axes(&self) -> (Vec2, Vec2) {
+        let x_axis = Vec2::from_angle(self.angle);
+        (x_axis, x_axis.perp())
+    }
+
+    /// Counter-clockwise with y up, starting from the corner at minus both extents.
+    pub fn corners(&self) -> [Vec2; 4] {
+        let (x_axis, y_axis) = self.axes();
+        let x = x_axis * self.half_extents.x;
+        let y = y_axis * self.half_extents.y;
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    pub fn area(&self) -> f32 {
+        4.0 * self.half_extents.x * self.half_extents.y
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let (x_axis, y_axis) = self.axes();
+        let offset = point - self.center;
+        offset.dot(x_axis).abs() <= self.half_extents.x
+            && offset.dot(y_axis).abs() <= self.half_extents.y
+    }
+
+    pub fn solid_color(&self) -> Vec<Vec2> {
+        Polygon::new(self.corners().to_vec()).solid_color(FillRule::NonZero)
+    }
+
+    pub fn empty(&self) -> Vec<Vec2> {
+        Polygon::new(self.corners().to_vec()).empty()
+    }
 }
@@ -0,0 +1,517 @@
+use crate::geometry::{Line, Rect};
+use crate::graphics::line;
+use crate::math::{Affine2, Vec2};
+
+/// Tolerance in pixels used by `solid`, finer than a pixel so the curve doesn't look faceted.
+const PIXEL_TOLERANCE: f32 = 0.25;
+/// Recursion limit for flattening, enough for curves far larger than the screen.
+const MAX_DEPTH: u32 = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct QuadraticBezier {
+    pub start: Vec2,
+    pub control: Vec2,
+    pub end: Vec2,
+}
+
+impl QuadraticBezier {
+    pub fn new(start: Vec2, control: Vec2, end: Vec2) -> Self {
+        Self {
+            start,
+            control,
+            end,
+        }
+    }
+
+    /// The same curve as a cubic, the heavier operations are shared through it.
+    pub fn to_cubic(self) -> CubicBezier {
+        CubicBezier::new(
+            self.start,
+            self.start + (self.control - self.start) * (2.0 / 3.0),
+            self.end + (self.control - self.end) * (2.0 / 3.0),
+            self.end,
+        )
+    }
+
+    pub fn point(&self, t: f32) -> Vec2 {
+        let inverse = 1.0 - t;
+        self.start * (inverse * inverse) + self.control * (2.0 * inverse * t) + self.end * (t * t)
+    }
+
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        (self.control - self.start) * (2.0 * (1.0 - t)) + (self.end - self.control) * (2.0 * t)
+    }
+
+    pub fn second_derivative(&self) -> Vec2 {
+        (self.end - self.control * 2.0 + self.start) * 2.0
+    }
+
+    /// The parts before and after `t`.
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let left = self.start.lerp(self.control, t);
+        let right = self.control.lerp(self.end, t);
+        let middle = left.lerp(right, t);
+        (
+            Self::new(self.start, left, middle),
+            Self::new(middle, right, self.end),
+        )
+    }
+
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::new(
+            transform.transform_point(self.start),
+            transform.transform_point(self.control),
+            transform.transform_point(self.end),
+        )
+    }
+
+    /// Exact top left and bottom right corners of the curve.
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        self.to_cubic().bounds()
+    }
+
+    pub fn bounding(&self) -> Rect {
+        self.to_cubic().bounding()
+    }
+
+    /// Curve parameter and position of the point on the curve closest to `point`.
+    pub fn nearest(&self, point: Vec2) -> (f32, Vec2) {
+        self.to_cubic().nearest(point)
+    }
+
+    pub fn length(&self) -> f32 {
+        self.to_cubic().length()
+    }
+
+    pub fn length_at(&self, t: f32) -> f32 {
+        self.to_cubic().length_at(t)
+    }
+
+    pub fn t_at_length(&self, length: f32) -> f32 {
+        self.to_cubic().t_at_length(length)
+    }
+
+    pub fn point_at_length(&self, length: f32) -> Vec2 {
+        self.point(self.t_at_length(length))
+    }
+
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        self.to_cubic().flatten(tolerance)
+    }
+
+    pub fn solid(&self) -> Vec<Vec2> {
+        self.to_cubic().solid()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubicBezier {
+    pub start: Vec2,
+    pub control1: Vec2,
+    pub control2: Vec2,
+    pub end: Vec2,
+}
+
+impl CubicBezier {
+    pub fn new(start: Vec2, control1: Vec2, control2: Vec2, end: Vec2) -> Self {
+        Self {
+            start,
+            control1,
+            control2,
+            end,
+        }
+    }
+
+    pub fn point(&self, t: f32) -> Vec2 {
+        let inverse = 1.0 - t;
+        self.start * (inverse * inverse * inverse)
+            + self.control1 * (3.0 * inverse * inverse * t)
+            + self.control2 * (3.0 * inverse * t * t)
+            + self.end * (t * t * t)
+    }
+
+    /// Velocity along the curve, its length is the speed at which `point` moves as `t` grows.
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        let inverse = 1.0 - t;
+        (self.control1 - self.start) * (3.0 * inverse * inverse)
+            + (self.control2 - self.control1) * (6.0 * inverse * t)
+            + (self.end - self.control2) * (3.0 * t * t)
+    }
+
+    pub fn second_derivative(&self, t: f32) -> Vec2 {
+        (self.control2 - self.control1 * 2.0 + self.start) * (6.0 * (1.0 - t))
+            + (self.end - self.control2 * 2.0 + self.control1) * (6.0 * t)
+    }
+
+    /// The parts before and after `t` (de Casteljau).
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let ab = self.start.lerp(self.control1, t);
+        let bc = self.control1.lerp(self.control2, t);
+        let cd = self.control2.lerp(self.end, t);
+        let abc = ab.lerp(bc, t);
+        let bcd = bc.lerp(cd, t);
+        let middle = abc.lerp(bcd, t);
+        (
+            Self::new(self.start, ab, abc, middle),
+            Self::new(middle, bcd, cd, self.end),
+        )
+    }
+
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::new(
+            transform.transform_point(self.start),
+            transform.transform_point(self.control1),
+            transform.transform_point(self.control2),
+            transform.transform_point(self.end),
+        )
+    }
+
+    /// Exact top left and bottom right corners of the curve, from the end points
+    /// and the points where the derivative is zero along an axis.
+    #[allow(clippy::suboptimal_flops)]
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        let mut min = self.start.min(self.end);
+        let mut max = self.start.max(self.end);
+        let a = self.control1 - self.start;
+        let b = self.control2 - self.control1;
+        let c = self.end - self.control2;
+        let roots_x = quadratic_roots(a.x - 2.0 * b.x + c.x, 2.0 * (b.x - a.x), a.x);
+        let roots_y = quadratic_roots(a.y - 2.0 * b.y + c.y, 2.0 * (b.y - a.y), a.y);
+        for t in roots_x.into_iter().chain(roots_y).flatten() {
+            if t > 0.0 && t < 1.0 {
+                let point = self.point(t);
+                min = min.min(point);
+                max = max.max(point);
+            }
+        }
+        (min, max)
+    }
+
+    pub fn bounding(&self) -> Rect {
+        let corners: [Vec2; 2] = self.bounds().into();
+        Rect::bounding(&corners)
+    }
+
+    /// Curve parameter and position of the point on the curve closest to `point`:
+    /// the best of a coarse scan is refined with Newton's method.
+    pub fn nearest(&self, point: Vec2) -> (f32, Vec2) {
+        const SAMPLES: usize = 32;
+        let mut best_t = (0..=SAMPLES)
+            .map(|i| i as f32 / SAMPLES as f32)
+            .min_by(|a, b| {
+                let a = self.point(*a).distance_squared(point);
+                let b = self.point(*b).distance_squared(point);
+                a.total_cmp(&b)
+            })
+            .unwrap_or(0.0);
+
+        for _ in 0..8 {
+            let offset = self.point(best_t) - point;
+            let derivative = self.derivative(best_t);
+            let numerator = offset.dot(derivative);
+            let denominator =
+                derivative.dot(derivative) + offset.dot(self.second_derivative(best_t));
+            if denominator.abs() < f32::EPSILON {
+                break;
+            }
+            let next = (best_t - numerator / denominator).clamp(0.0, 1.0);
+            if (next - best_t).abs() < 1e-6 {
+                best_t = next;
+                break;
+            }
+            best_t = next;
+        }
+        (best_t, self.point(best_t))
+    }
+
+    pub fn length(&self) -> f32 {
+        self.length_at(1.0)
+    }
+
+    /// Arc length from the start to `t`, by Gauss-Legendre quadrature of the speed.
+    pub fn length_at(&self, t: f32) -> f32 {
+        const NODES: [(f32, f32); 5] = [
+            (0.0, 0.568_888_9),
+            (-0.538_469_3, 0.478_628_67),
+            (0.538_469_3, 0.478_628_67),
+            (-0.906_179_8, 0.236_926_88),
+            (0.906_179_8, 0.236_926_88),
+        ];
+        const PIECES: usize = 4;
+        let t = t.clamp(0.0, 1.0);
+        let piece = t / PIECES as f32;
+        (0..PIECES)
+            .map(|i| {
+                let center = piece * (i as f32 + 0.5);
+                NODES
+                    .iter()
+                    .map(|(node, weight)| {
+                        weight * self.derivative(center + node * piece / 2.0).len()
+                    })
+                    .sum::<f32>()
+                    * piece
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// Inverse of `length_at`, stepping the result by equal lengths moves at constant speed.
+    pub fn t_at_length(&self, length: f32) -> f32 {
+        let total = self.length();
+        if length <= 0.0 || total <= 0.0 {
+            return 0.0;
+        }
+        if length >= total {
+            return 1.0;
+        }
+        let (mut low, mut high) = (0.0, 1.0);
+        let mut t = length / total;
+        for _ in 0..16 {
+            let error = self.length_at(t) - length;
+            if error.abs() < 1e-3 {
+                break;
+            }
+            if error > 0.0 {
+                high = t;
+            } else {
+                low = t;
+            }
+            // Newton step, falling back to bisection when it leaves the bracket.
+            let speed = self.derivative(t).len();
+            let next = t - error / speed;
+            t = if speed > 0.0 && next > low && next < high {
+                next
+            } else {
+                f32::midpoint(low, high)
+            };
+        }
+        t
+    }
+
+    pub fn point_at_length(&self, length: f32) -> Vec2 {
+        self.point(self.t_at_length(length))
+    }
+
+    /// Points `spacing` apart along the curve, measured along the curve.
+    pub fn even_points(&self, spacing: f32) -> Vec<Vec2> {
+        assert!(spacing > 0.0, "Spacing should be positive");
+        let total = self.length();
+        let count = (total / spacing) as usize;
+        (0..=count)
+            .map(|i| self.point_at_length(i as f32 * spacing))
+            .collect()
+    }
+
+    /// Polyline from start to end that never strays further than `tolerance` from the curve.
+    /// Flat parts get few points, tight bends get many.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        let mut points = vec![self.start];
+        self.flatten_into(tolerance.max(f32::EPSILON), MAX_DEPTH, &mut points);
+        points
+    }
+
+    fn flatten_into(&self, tolerance: f32, depth: u32, points: &mut Vec<Vec2>) {
+        if depth == 0 || self.flatness() <= tolerance {
+            points.push(self.end);
+            return;
+        }
+        let (left, right) = self.split(0.5);
+        left.flatten_into(tolerance, depth - 1, points);
+        right.flatten_into(tolerance, depth - 1, points);
+    }
+
+    /// Upper bound for how far the curve strays from its chord, the control points bound the curve.
+    /// Measured to the segment rather than its line, so control points beyond the ends count.
+    fn flatness(&self) -> f32 {
+        let chord = Line::new(self.start, self.end);
+        chord
+            .distance(self.control1)
+            .max(chord.distance(self.control2))
+    }
+
+    pub fn solid(&self) -> Vec<Vec2> {
+        let polyline = self.flatten(PIXEL_TOLERANCE);
+        let mut points = Vec::new();
+        for segment in polyline.windows(2) {
+            points.extend(line(segment[0], segment[1]));
+        }
+        if let Some(end) = polyline.last() {
+            points.push(end.round());
+        }
+        points
+    }
+}
+
+/// Real roots of `a * t^2 + b * t + c`, also handles the linear case.
+#[allow(clippy::suboptimal_flops)]
+fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+    if a.abs() < 1e-6 {
+        if b.abs() < 1e-6 {
+            return [None, None];
+        }
+        return [Some(-c / b), None];
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+    let root = discriminant.sqrt();
+    [Some((-b + root) / (2.0 * a)), Some((-b - root) / (2.0 * a))]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave() -> CubicBezier {
+        CubicBezier::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(30.0, 80.0),
+            Vec2::new(70.0, -80.0),
+            Vec2::new(100.0, 0.0),
+        )
+    }
+
+    /// Distance from `point` to the closest segment of `polyline`.
+    fn polyline_distance(polyline: &[Vec2], point: Vec2) -> f32 {
+        polyline
+            .windows(2)
+            .map(|segment| Line::new(segment[0], segment[1]).distance(point))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    fn samples(count: usize) -> impl Iterator<Item = f32> {
+        (0..=count).map(move |i| i as f32 / count as f32)
+    }
+
+    #[test]
+    fn quadratic_matches_its_cubic() {
+        let quadratic = QuadraticBezier::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(50.0, 100.0),
+            Vec2::new(100.0, 0.0),
+        );
+        let cubic = quadratic.to_cubic();
+        for t in samples(10) {
+            assert!(quadratic.point(t).distance(cubic.point(t)) < 1e-3, "{t}");
+            assert!(
+                quadratic.derivative(t).distance(cubic.derivative(t)) < 1e-3,
+                "{t}"
+            );
+        }
+        assert!((quadratic.point(0.5).y - 50.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn split_halves_follow_the_curve() {
+        let curve = wave();
+        let (left, right) = curve.split(0.3);
+        for t in samples(10) {
+            assert!(left.point(t).distance(curve.point(t * 0.3)) < 1e-3);
+            assert!(right.point(t).distance(curve.point(t.mul_add(0.7, 0.3))) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn bounds_contain_the_curve_tightly() {
+        let curve = wave();
+        let (min, max) = curve.bounds();
+        let (mut sampled_min, mut sampled_max) = (curve.start, curve.start);
+        for t in samples(1000) {
+            let point = curve.point(t);
+            assert!(
+                point.x >= min.x - 1e-3 && point.y >= min.y - 1e-3,
+                "{point:?}"
+            );
+            assert!(
+                point.x <= max.x + 1e-3 && point.y <= max.y + 1e-3,
+                "{point:?}"
+            );
+            sampled_min = sampled_min.min(point);
+            sampled_max = sampled_max.max(point);
+        }
+        // The extremes lie between samples, so only allow a little slack.
+        assert!(min.distance(sampled_min) < 0.01 && max.distance(sampled_max) < 0.01);
+        assert!(min.y < -20.0 && max.y > 20.0);
+    }
+
+    #[test]
+    fn straight_curve_length_is_the_chord() {
+        let curve = CubicBezier::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(20.0, 0.0),
+            Vec2::new(30.0, 0.0),
+        );
+        assert!((curve.length() - 30.0).abs() < 1e-3);
+        assert!((curve.length_at(0.5) - 15.0).abs() < 1e-3);
+        assert!((curve.t_at_length(10.0) - 1.0 / 3.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn length_matches_a_fine_polyline() {
+        let curve = wave();
+        let points: Vec<Vec2> = samples(10_000).map(|t| curve.point(t)).collect();
+        let polyline: f32 = points.windows(2).map(|s| s[0].distance(s[1])).sum();
+        assert!((curve.length() - polyline).abs() < polyline * 1e-3);
+    }
+
+    #[test]
+    fn even_points_are_evenly_spaced() {
+        let curve = wave();
+        let points = curve.even_points(10.0);
+        assert_eq!(points.len(), (curve.length() / 10.0) as usize + 1);
+        for pair in points.windows(2) {
+            // Chords are a bit shorter than the arc between them.
+            let chord = pair[0].distance(pair[1]);
+            assert!(chord > 9.0 && chord <= 10.01, "{chord}");
+        }
+    }
+
+    #[test]
+    fn flattening_stays_within_tolerance() {
+        let curve = wave();
+        for tolerance in [2.0, 0.5, 0.1] {
+            let polyline = curve.flatten(tolerance);
+            assert_eq!(polyline.first(), Some(&curve.start));
+            assert_eq!(polyline.last(), Some(&curve.end));
+            for t in samples(500) {
+                let distance = polyline_distance(&polyline, curve.point(t));
+                assert!(distance <= tolerance, "{tolerance} {t} {distance}");
+            }
+        }
+        assert!(curve.flatten(0.1).len() > curve.flatten(2.0).len());
+    }
+
+    #[test]
+    fn flattening_follows_control_points_beyond_the_ends() {
+        // All points on one line, but the curve overshoots both ends of its chord.
+        let curve = CubicBezier::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(-100.0, 0.0),
+            Vec2::new(200.0, 0.0),
+            Vec2::new(100.0, 0.0),
+        );
+        let polyline = curve.flatten(0.5);
+        for t in samples(500) {
+            assert!(polyline_distance(&polyline, curve.point(t)) <= 0.5, "{t}");
+        }
+    }
+
+    #[test]
+    fn nearest_matches_a_scan() {
+        let curve = wave();
+        for point in [
+            Vec2::new(50.0, 50.0),
+            Vec2::new(-20.0, 10.0),
+            Vec2::new(80.0, -10.0),
+        ] {
+            let (t, nearest) = curve.nearest(point);
+            assert!(nearest.distance(curve.point(t)) < 1e-4);
+            let scanned = samples(10_000)
+                .map(|s| curve.point(s).distance(point))
+                .fold(f32::INFINITY, f32::min);
+            assert!(nearest.distance(point) <= scanned + 1e-3, "{point:?}");
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Boolean operations between polygons by overlaying their edges: every edge is split where
+//! it meets another, each piece is kept if the result is inside on exactly one of its sides,
+//! and the kept pieces are linked back into rings.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
+
+use crate::geometry::polygon::{clean_ring, FillRule, Polygon};
+use crate::geometry::Line;
+use crate::math::Vec2;
+use crate::predicates::orient2d;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    /// The first polygon with the second cut out of it.
+    Difference,
+    /// Covered by exactly one of the polygons.
+    Xor,
+}
+
+impl BooleanOp {
+    fn keeps(self, in_first: bool, in_second: bool) -> bool {
+        match self {
+            Self::Union => in_first || in_second,
+            Self::Intersection => in_first && in_second,
+            Self::Difference => in_first && !in_second,
+            Self::Xor => in_first != in_second,
+        }
+    }
+}
+
+impl Polygon {
+    /// Both polygons are read with the even-odd rule. The result has outer rings wound
+    /// counter-clockwise (y up) and holes the other way, like `orient` leaves them.
+    pub fn boolean(&self, other: &Self, op: BooleanOp) -> Self {
+        overlay(self.edges().chain(other.edges()), |point| {
+            op.keeps(
+                self.contains(point, FillRule::EvenOdd),
+                other.contains(point, FillRule::EvenOdd),
+            )
+        })
+    }
+
+    /// The area `rule` fills, as rings that don't overlap or cross, wound like `boolean` leaves them.
+    pub fn simplified(&self, rule: FillRule) -> Self {
+        overlay(self.edges(), |point| self.contains(point, rule))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    pub fn xor(&self, other: &Self) -> Self {
+        self.boolean(other, BooleanOp::Xor)
+    }
+}
+
+/// Rings around where `inside` holds, out of the pieces of the edges with it on one side only.
+fn overlay(edges: impl Iterator<Item = (Vec2, Vec2)>, inside: impl Fn(Vec2) -> bool) -> Polygon {
+    let segments = split_edges(edges);
+    let mut kept: Vec<(Vec2, Vec2)> = Vec::new();
+    for (index, &(a, b)) in segments.iter().enumerate() {
+        let middle = (a + b) / 2.0;
+        let nearest = segments
+            .iter()
+            .enumerate()
+            .filter(|(other_index, _)| *other_index != index)
+            .map(|(_, (from, to))| Line::new(*from, *to).distance(middle))
+            .fold(f32::INFINITY, f32::min);
+        // Close enough that no other edge is in between, but not lost to rounding.
+        let floor = middle.abs().x.max(middle.abs().y).max(1.0) * 16.0 * f32::EPSILON;
+        let offset = (a.distance(b) / 4.0).min(nearest / 2.0).max(floor);
+        let normal = (b - a).perp().normalize() * offset;
+        match (inside(middle + normal), inside(middle - normal)) {
+            (true, false) => kept.push((a, b)),
+            (false, true) => kept.push((b, a)),
+            _ => {}
+        }
+    }
+    Polygon::from_rings(link_rings(&kept))
+}
+
+/// Pieces of the edges that only meet at their ends, each piece once whichever way it ran.
+fn split_edges(edges: impl Iterator<Item = (Vec2, Vec2)>) -> Vec<(Vec2, Vec2)> {
+    let edges: Vec<(Vec2, Vec2)> = edges.filter(|(a, b)| a != b).collect();
+    let mut cuts: Vec<Vec<Vec2>> = edges.iter().map(|(a, b)| vec![*a, *b]).collect();
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            let (p1, p2) = edges[i];
+            let (q1, q2) = edges[j];
+            let sides = [
+                orient2d(p1, p2, q1),
+                orient2d(p1, p2, q2),
+                orient2d(q1, q2, p1),
+                orient2d(q1, q2, p2),
+            ];
+            if sides[0] == sides[1].reverse()
+                && sides[2] == sides[3].reverse()
+                && !sides.contains(&Ordering::Equal)
+            {
+                // Rounding can put the crossing just past an end, it goes onto that end instead
+                // so the pieces keep their direction.
+                let crossing = [edges[i], edges[j]].into_iter().fold(
+                    crossing_point(edges[i], edges[j]),
+                    |point, (a, b)| {
+                        let position = along(point, (a, b));
+                        if position <= 0.0 {
+                            a
+                        } else if position >= 1.0 {
+                            b
+                        } else {
+                            point
+                        }
+                    },
+                );
+                cuts[i].push(crossing);
+                cuts[j].push(crossing);
+                continue;
+            }
+            // Ends lying on the other edge, that covers touching and overlapping edges.
+            for (side, point, edge, cut) in [
+                (sides[0], q1, edges[i], i),
+                (sides[1], q2, edges[i], i),
+                (sides[2], p1, edges[j], j),
+                (sides[3], p2, edges[j], j),
+            ] {
+                if side == Ordering::Equal && within(point, edge) {
+                    cuts[cut].push(point);
+                }
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut pieces = Vec::new();
+    for ((a, b), mut points) in edges.into_iter().zip(cuts) {
+        points.sort_by(|p, q| along(*p, (a, b)).total_cmp(&along(*q, (a, b))));
+        points.dedup();
+        for pair in points.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let key = if key(from) < key(to) {
+                (key(from), key(to))
+            } else {
+                (key(to), key(from))
+            };
+            if seen.insert(key) {
+                pieces.push((from, to));
+            }
+        }
+    }
+    pieces
+}
+
+fn key(point: Vec2) -> (u32, u32) {
+    (point.x.to_bits(), point.y.to_bits())
+}
+
+/// Where two edges that properly cross meet, computed in f64 to lose less to rounding.
+#[allow(clippy::suboptimal_flops)]
+fn crossing_point((p1, p2): (Vec2, Vec2), (q1, q2): (Vec2, Vec2)) -> Vec2 {
+    let widen = |point: Vec2| (f64::from(point.x), f64::from(point.y));
+    let ((px, py), (rx, ry)) = (widen(p1), widen(p2 - p1));
+    let ((qx, qy), (sx, sy)) = (widen(q1), widen(q2 - q1));
+    let t = ((qx - px) * sy - (qy - py) * sx) / (rx * sy - ry * sx);
+    Vec2::new((px + rx * t) as f32, (py + ry * t) as f32)
+}
+
+/// Where the point projects onto the edge, 0 at its start and 1 at its end.
+/// In f64, cuts closer together than an f32 rounding step still come out in order.
+#[allow(clippy::suboptimal_flops)]
+fn along(point: Vec2, (a, b): (Vec2, Vec2)) -> f64 {
+    let widen = |point: Vec2| (f64::from(point.x), f64::from(point.y));
+    let ((ax, ay), (bx, by), (px, py)) = (widen(a), widen(b), widen(point));
+    let (dx, dy) = (bx - ax, by - ay);
+    ((px - ax) * dx + (py - ay) * dy) / (dx * dx + dy * dy)
+}
+
+/// Whether a point known to be on the edge's line is between its ends.
+fn within(point: Vec2, (a, b): (Vec2, Vec2)) -> bool {
+    point.x >= a.x.min(b.x)
+        && point.x <= a.x.max(b.x)
+        && point.y >= a.y.min(b.y)
+        && point.y <= a.y.max(b.y)
+}
+
+/// Follows the directed edges into closed rings. Where several rings meet at a vertex, the
+/// sharpest right turn is taken, that keeps rings that only touch apart.
+fn link_rings(edges: &[(Vec2, Vec2)]) -> Vec<Vec<Vec2>> {
+    let mut outgoing: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (index, (from, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(*from)).or_default().push(index);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut ring = vec![edges[start].0];
+        let mut current = start;
+        loop {
+            let (from, to) = edges[current];
+            let back = from - to;
+            let next = outgoing.get(&key(to)).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|candidate| !used[*candidate] || *candidate == start)
+                    .min_by(|x, y| {
+                        clockwise_turn(back, edges[*x].1 - to)
+                            .total_cmp(&clockwise_turn(back, edges[*y].1 - to))
+                    })
+            });
+            match next {
+                Some(next) if next == start => {
+                    let ring = clean_ring(&ring);
+                    if !ring.is_empty() {
+                        rings.push(ring);
+                    }
+                    break;
+                }
+                Some(next) => {
+                    used[next] = true;
+                    ring.push(to);
+                    current = next;
+                }
+                // Rounding left the ring open, it is dropped.
+                None => break,
+            }
+        }
+    }
+    rings
+}
+
+/// Clockwise angle from `from` to `to` in (0, TAU], turning all the way around comes last.
+fn clockwise_turn(from: Vec2, to: Vec2) -> f32 {
+    let angle = -from.angle_between(to);
+    if angle <= 0.0 {
+        angle + TAU
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    fn square(x: f32, y: f32, size: f32) -> Polygon {
+        Polygon::new(vec![
+            Vec2::new(x, y),
+            Vec2::new(x + size, y),
+            Vec2::new(x + size, y + size),
+            Vec2::new(x, y + size),
+        ])
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    const OPS: [BooleanOp; 4] = [
+        BooleanOp::Union,
+        BooleanOp::Intersection,
+        BooleanOp::Difference,
+        BooleanOp::Xor,
+    ];
+
+    #[test]
+    fn overlapping_squares() {
+        let (a, b) = (square(0.0, 0.0, 4.0), square(2.0, 1.0, 4.0));
+        assert!(close(a.union(&b).area(), 26.0));
+        assert!(close(a.intersection(&b).area(), 6.0));
+        assert!(close(a.difference(&b).area(), 10.0));
+        assert!(close(a.xor(&b).area(), 20.0));
+        for op in OPS {
+            let result = a.boolean(&b, op);
+            // Oriented like `Polygon::orient` leaves it.
+            assert!(close(result.signed_area(), result.area()), "{op:?}");
+        }
+        assert_eq!(a.union(&b).rings.len(), 1);
+        assert_eq!(a.union(&b).rings[0].len(), 8);
+    }
+
+    #[test]
+    fn results_cover_what_the_op_keeps() {
+        let random = Random::new(4);
+        let star: Vec<Vec2> = (0..10)
+            .map(|i| {
+                let radius = if i % 2 == 0 { 10.0 } else { 4.0 };
+                Vec2::new(11.0, 9.0) + Vec2::from_angle(i as f32 * TAU / 10.0) * radius
+            })
+            .collect();
+        let a = Polygon::new(star).with_hole(square(9.0, 7.0, 3.0).rings[0].clone());
+        let b = square(3.0, 2.0, 9.0).with_hole(square(5.0, 4.0, 2.0).rings[0].clone());
+        for op in OPS {
+            let result = a.boolean(&b, op);
+            let mut checked = 0;
+            while checked < 500 {
+                let point = Vec2::new(
+                    random.random_range(0.0, 22.0),
+                    random.random_range(-2.0, 20.0),
+                );
+                // Stay clear of the edges, which may land either way.
+                let clearance = a
+                    .edges()
+                    .chain(b.edges())
+                    .map(|(from, to)| Line::new(from, to).distance(point))
+                    .fold(f32::INFINITY, f32::min);
+                if clearance < 1e-2 {
+                    continue;
+                }
+                checked += 1;
+                let expected = op.keeps(
+                    a.contains(point, FillRule::EvenOdd),
+                    b.contains(point, FillRule::EvenOdd),
+                );
+                assert_eq!(
+                    result.contains(point, FillRule::NonZero),
+                    expected,
+                    "{op:?} {point:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn areas_add_up() {
+        let a = square(0.0, 0.0, 5.0).with_hole(square(1.0, 1.0, 2.0).rings[0].clone());
+        let b = Polygon::new(vec![
+            Vec2::new(2.0, -1.0),
+            Vec2::new(8.0, 2.0),
+            Vec2::new(3.0, 7.0),
+        ]);
+        let union = a.union(&b).area();
+        let intersection = a.intersection(&b).area();
+        assert!(close(union + intersection, a.area() + b.area()));
+        assert!(close(a.xor(&b).area(), union - intersection));
+        assert!(close(a.difference(&b).area(), a.area() - intersection));
+    }
+
+    #[test]
+    fn shared_edges_and_corners() {
+        // A shared edge disappears, only the rectangle's corners are left.
+        let side_by_side = square(0.0, 0.0, 2.0).union(&square(2.0, 0.0, 2.0));
+        assert_eq!(side_by_side.rings.len(), 1);
+        assert_eq!(side_by_side.rings[0].len(), 4);
+        assert!(close(side_by_side.area(), 8.0));
+        assert!(side_by_side
+            .intersection(&square(4.0, 0.0, 2.0))
+            .rings
+            .is_empty());
+
+        // Squares touching at a corner stay two rings.
+        let diagonal = square(0.0, 0.0, 2.0).union(&square(2.0, 2.0, 2.0));
+        assert_eq!(diagonal.rings.len(), 2);
+        assert!(close(diagonal.area(), 8.0));
+    }
+
+    #[test]
+    fn cutting_a_hole() {
+        let result = square(0.0, 0.0, 6.0).difference(&square(2.0, 2.0, 2.0));
+        assert_eq!(result.rings.len(), 2);
+        assert!(close(result.area(), 32.0));
+        assert!(close(result.signed_area(), 32.0));
+        assert!(!result.contains(Vec2::new(3.0, 3.0), FillRule::NonZero));
+        assert!(square(0.0, 0.0, 1.0)
+            .intersection(&square(5.0, 5.0, 1.0))
+            .rings
+            .is_empty());
+    }
+
+    #[test]
+    fn simplified_merges_overlapping_rings() {
+        let rings = Polygon::from_rings(vec![
+            square(0.0, 0.0, 4.0).rings[0].clone(),
+            square(2.0, 2.0, 4.0).rings[0].clone(),
+        ]);
+        let non_zero = rings.simplified(FillRule::NonZero);
+        assert_eq!(non_zero.rings.len(), 1);
+        assert!(close(non_zero.area(), 28.0));
+        let even_odd = rings.simplified(FillRule::EvenOdd);
+        assert!(close(even_odd.area(), 24.0));
+        assert!(!even_odd.contains(Vec2::new(3.0, 3.0), FillRule::NonZero));
+    }
+
+    #[test]
+    fn simplified_splits_a_bow_tie() {
+        let bow_tie = Polygon::new(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 4.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(0.0, 4.0),
+        ]);
+        // The two halves wind opposite ways, so the signed area cancels out.
+        assert!(close(bow_tie.signed_area(), 0.0));
+        let simplified = bow_tie.simplified(FillRule::NonZero);
+        assert_eq!(simplified.rings.len(), 2);
+        assert!(close(simplified.area(), 8.0));
+        assert!(close(simplified.signed_area(), 8.0));
+    }
+
+    #[test]
+    fn crossings_rounded_onto_an_end() {
+        // The circle's edge passes within 1e-8 of the box corner at (0, -1), the crossing
+        // rounds onto the corner while the edge itself ends slightly past it.
+        let circle = Polygon::new(
+            (0..8)
+                .map(|i| Vec2::from_angle(i as f32 * TAU / 8.0))
+                .collect(),
+        );
+        let body = Polygon::new(vec![
+            Vec2::new(0.0, -1.0),
+            Vec2::new(10.0, -1.0),
+            Vec2::new(10.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ]);
+        let union = body.union(&circle);
+        assert_eq!(union.rings.len(), 1);
+        assert!(close(union.area(), 20.0 + circle.area() / 2.0));
+    }
+}
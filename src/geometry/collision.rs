@@ -0,0 +1,539 @@
+//! Collision between convex shapes by the separating axis theorem: two convex shapes
+//! overlap unless their shadows on some axis are apart, and only a few axes need a look.
+
+use crate::geometry::hull::convex_hull;
+use crate::geometry::intersect::{line_circle, ray_segment, Ray};
+use crate::geometry::{Circle, Line, OrientedRect, Rect, Triangle};
+use crate::math::Vec2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Convex {
+    /// Corners counter-clockwise (y up) as `convex_hull` gives them, see `Convex::polygon`.
+    Polygon(Vec<Vec2>),
+    Circle(Circle),
+}
+
+impl From<Rect> for Convex {
+    fn from(rect: Rect) -> Self {
+        let corners: [Vec2; 4] = rect.corners().into();
+        Self::polygon(&corners)
+    }
+}
+
+impl From<OrientedRect> for Convex {
+    fn from(rect: OrientedRect) -> Self {
+        Self::polygon(&rect.corners())
+    }
+}
+
+impl From<Triangle> for Convex {
+    fn from(triangle: Triangle) -> Self {
+        let Triangle { a, b, c } = triangle;
+        Self::polygon(&[a, b, c])
+    }
+}
+
+impl From<Circle> for Convex {
+    fn from(circle: Circle) -> Self {
+        Self::Circle(circle)
+    }
+}
+
+impl Convex {
+    /// The convex hull of the points, so they may come in any order.
+    pub fn polygon(points: &[Vec2]) -> Self {
+        Self::Polygon(convex_hull(points))
+    }
+
+    pub fn translate(&self, offset: Vec2) -> Self {
+        match self {
+            Self::Polygon(corners) => {
+                Self::Polygon(corners.iter().map(|corner| *corner + offset).collect())
+            }
+            Self::Circle(circle) => {
+                Self::Circle(Circle::new(circle.center + offset, circle.radius))
+            }
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        match self {
+            Self::Polygon(corners) => {
+                corners
+                    .iter()
+                    .fold(Vec2::default(), |sum, corner| sum + *corner)
+                    / corners.len().max(1) as f32
+            }
+            Self::Circle(circle) => circle.center,
+        }
+    }
+
+    /// Smallest and largest `dot` with the axis over the shape.
+    fn project(&self, axis: Vec2) -> (f32, f32) {
+        match self {
+            Self::Polygon(corners) => {
+                corners
+                    .iter()
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), corner| {
+                        let along = corner.dot(axis);
+                        (low.min(along), high.max(along))
+                    })
+            }
+            Self::Circle(circle) => {
+                let along = circle.center.dot(axis);
+                (along - circle.radius, along + circle.radius)
+            }
+        }
+    }
+
+    /// Corners and how far the shape reaches around them, a circle is one rounded corner.
+    fn rounded(&self) -> (Vec<Vec2>, f32) {
+        match self {
+            Self::Polygon(corners) => (corners.clone(), 0.0),
+            Self::Circle(circle) => (vec![circle.center], circle.radius),
+        }
+    }
+}
+
+/// Edges of a counter-clockwise (y up) polygon with their outward unit normals.
+fn faces(corners: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2, Vec2)> + '_ {
+    (0..corners.len()).filter_map(|i| {
+        let (from, to) = (corners[i], corners[(i + 1) % corners.len()]);
+        // The inside is on the left, where `perp` turns to.
+        (from != to).then(|| (from, to, -(to - from).perp().normalize()))
+    })
+}
+
+/// How two overlapping shapes touch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing from the first shape towards the second.
+    pub normal: Vec2,
+    /// How far the second shape has to move along `normal` to only touch the first.
+    pub depth: f32,
+    /// One or two points of one shape that are deepest inside the other.
+    pub points: Vec<Vec2>,
+}
+
+/// Where the shapes overlap, `None` when they are apart. Shapes that only touch
+/// collide with zero depth.
+pub fn collide(first: &Convex, second: &Convex) -> Option<Contact> {
+    // Face normals of polygons, and the line from a circle's center to the nearest corner
+    // or center of the other shape.
+    let mut candidates: Vec<(Vec2, Owner)> = Vec::new();
+    for (shape, other, owner) in [
+        (first, second, Owner::First),
+        (second, first, Owner::Second),
+    ] {
+        match shape {
+            Convex::Polygon(corners) => {
+                candidates.extend(faces(corners).map(|(_, _, normal)| (normal, owner)));
+            }
+            Convex::Circle(circle) => {
+                let (corners, _) = other.rounded();
+                let nearest = corners.into_iter().min_by(|a, b| {
+                    a.distance_squared(circle.center)
+                        .total_cmp(&b.distance_squared(circle.center))
+                });
+                if let Some(nearest) = nearest.filter(|nearest| *nearest != circle.center) {
+                    candidates.push(((nearest - circle.center).normalize(), Owner::Neither));
+                }
+            }
+        }
+    }
+    if candidates.is_empty() {
+        let between = second.center() - first.center();
+        let axis = if between == Vec2::default() {
+            Vec2::new(1.0, 0.0)
+        } else {
+            between.normalize()
+        };
+        candidates.push((axis, Owner::Neither));
+    }
+
+    let mut best: Option<(f32, Vec2, Owner)> = None;
+    for (axis, owner) in candidates {
+        let (first_low, first_high) = first.project(axis);
+        let (second_low, second_high) = second.project(axis);
+        // Pushing the second shape whichever way along the axis is shorter.
+        let (overlap, normal) = if first_high - second_low <= second_high - first_low {
+            (first_high - second_low, axis)
+        } else {
+            (second_high - first_low, -axis)
+        };
+        if overlap < 0.0 {
+            return None;
+        }
+        if best.is_none_or(|(depth, _, _)| overlap < depth) {
+            best = Some((overlap, normal, owner));
+        }
+    }
+    let (depth, normal, owner) = best?;
+
+    let points = match (first, second) {
+        (Convex::Polygon(first_corners), Convex::Polygon(second_corners)) => {
+            // The face the normal came from is the reference, the other polygon's face
+            // turned most against it is clipped to it.
+            if owner == Owner::Second {
+                clip_contacts(second_corners, first_corners, -normal)
+            } else {
+                clip_contacts(first_corners, second_corners, normal)
+            }
+        }
+        (_, Convex::Circle(circle)) => vec![circle.center - normal * circle.radius],
+        (Convex::Circle(circle), Convex::Polygon(_)) => {
+            vec![circle.center + normal * circle.radius]
+        }
+    };
+    Some(Contact {
+        normal,
+        depth,
+        points,
+    })
+}
+
+/// Which shape an axis is a face normal of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Owner {
+    First,
+    Second,
+    Neither,
+}
+
+/// Corners of the incident face inside the reference polygon, `normal` points out of the reference.
+fn clip_contacts(reference: &[Vec2], incident: &[Vec2], normal: Vec2) -> Vec<Vec2> {
+    let most = |corners: &[Vec2], direction: Vec2| {
+        faces(corners)
+            .max_by(|a, b| a.2.dot(direction).total_cmp(&b.2.dot(direction)))
+            .map(|(from, to, _)| (from, to))
+    };
+    let (Some((reference_from, reference_to)), Some((incident_from, incident_to))) =
+        (most(reference, normal), most(incident, -normal))
+    else {
+        return deepest(incident, normal);
+    };
+
+    // Cut the incident face down to the reference face's width.
+    let tangent = (reference_to - reference_from).normalize();
+    let mut points = vec![incident_from, incident_to];
+    for (offset, direction) in [
+        (reference_from.dot(tangent), tangent),
+        (-reference_to.dot(tangent), -tangent),
+    ] {
+        points = clip(&points, |point| point.dot(direction) - offset);
+    }
+    let points: Vec<Vec2> = points
+        .into_iter()
+        .filter(|point| (*point - reference_from).dot(normal) <= 0.0)
+        .collect();
+    if points.is_empty() {
+        deepest(incident, normal)
+    } else {
+        points
+    }
+}
+
+/// The part of a segment where `side` is not negative.
+fn clip(points: &[Vec2], side: impl Fn(Vec2) -> f32) -> Vec<Vec2> {
+    let [from, to] = points[..] else {
+        return points.to_vec();
+    };
+    let (from_side, to_side) = (side(from), side(to));
+    let mut clipped = Vec::with_capacity(2);
+    if from_side >= 0.0 {
+        clipped.push(from);
+    }
+    if to_side >= 0.0 {
+        clipped.push(to);
+    }
+    if (from_side < 0.0) != (to_side < 0.0) {
+        clipped.push(from.lerp(to, from_side / (from_side - to_side)));
+    }
+    clipped
+}
+
+fn deepest(corners: &[Vec2], normal: Vec2) -> Vec<Vec2> {
+    corners
+        .iter()
+        .copied()
+        .min_by(|a, b| a.dot(normal).total_cmp(&b.dot(normal)))
+        .into_iter()
+        .collect()
+}
+
+/// When two moving shapes first touch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Impact {
+    /// Part of the velocities covered before the shapes touch, in [0, 1].
+    pub time: f32,
+    /// Unit vector pointing from the first shape towards the second where they touch.
+    pub normal: Vec2,
+}
+
+/// First moment the shapes touch while each moves by its velocity, `None` if they stay apart.
+/// Shapes overlapping from the start hit at time 0 with the normal of `collide`.
+pub fn sweep(
+    first: &Convex,
+    first_velocity: Vec2,
+    second: &Convex,
+    second_velocity: Vec2,
+) -> Option<Impact> {
+    if let Some(contact) = collide(first, second) {
+        return Some(Impact {
+            time: 0.0,
+            normal: contact.normal,
+        });
+    }
+    // The shapes touch once the second shape's offsets from the first, swept by
+    // the relative motion, cover the origin: a ray cast against the rounded
+    // Minkowski difference.
+    let (first_corners, first_radius) = first.rounded();
+    let (second_corners, second_radius) = second.rounded();
+    let differences: Vec<Vec2> = second_corners
+        .iter()
+        .flat_map(|b| first_corners.iter().map(move |a| *b - *a))
+        .collect();
+    let difference = convex_hull(&differences);
+    let radius = first_radius + second_radius;
+    let ray = Ray::new(Vec2::default(), first_velocity - second_velocity);
+
+    let line = Line::new(ray.origin, ray.point_at(1.0));
+    let face_hits = faces(&difference).filter_map(|(from, to, outward)| {
+        let offset = outward * radius;
+        ray_segment(&ray, &Line::new(from + offset, to + offset)).map(|hit| (hit.t, outward))
+    });
+    let corner_hits = difference
+        .iter()
+        .filter(|_| radius > 0.0)
+        .flat_map(|corner| line_circle(&line, &Circle::new(*corner, radius)))
+        .map(|hit| (hit.t, hit.normal));
+    face_hits
+        .chain(corner_hits)
+        .filter(|(time, _)| (0.0..=1.0).contains(time))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(time, outward)| Impact {
+            time,
+            normal: -outward,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::intersect::{distance, Shape};
+    use crate::geometry::polygon::Polygon;
+    use crate::geometry::Size;
+    use crate::random::Random;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-4
+    }
+
+    fn square(x: f32, y: f32, size: f32) -> Convex {
+        Rect::new(Vec2::new(x, y), Size::new(size as u32, size as u32)).into()
+    }
+
+    fn shape(convex: &Convex) -> Shape {
+        match convex {
+            Convex::Polygon(corners) => Polygon::new(corners.clone()).into(),
+            Convex::Circle(circle) => (*circle).into(),
+        }
+    }
+
+    /// Random triangles, turned rectangles and circles around the origin.
+    fn scatter(seed: u64, count: usize) -> Vec<Convex> {
+        let random = Random::new(seed);
+        let point = || {
+            Vec2::new(
+                random.random_range(-6.0, 6.0),
+                random.random_range(-6.0, 6.0),
+            )
+        };
+        (0..count)
+            .map(|i| match i % 3 {
+                0 => Triangle::new(point(), point(), point()).into(),
+                1 => OrientedRect::new(
+                    point(),
+                    Vec2::new(random.random_range(0.5, 3.0), random.random_range(0.5, 3.0)),
+                    random.random_range(0.0, 3.0),
+                )
+                .into(),
+                _ => Circle::new(point(), random.random_range(0.5, 3.0)).into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let contact = collide(&square(0.0, 0.0, 4.0), &square(3.0, 1.0, 4.0)).unwrap();
+        assert!(close(contact.normal, Vec2::new(1.0, 0.0)));
+        assert!((contact.depth - 1.0).abs() < 1e-5);
+        // The second square's left edge, cut to the first square's height.
+        assert_eq!(contact.points.len(), 2);
+        for point in &contact.points {
+            assert!((point.x - 3.0).abs() < 1e-5 && (1.0..=4.0).contains(&point.y));
+        }
+
+        // The other way around the normal turns.
+        let flipped = collide(&square(3.0, 1.0, 4.0), &square(0.0, 0.0, 4.0)).unwrap();
+        assert!(close(flipped.normal, Vec2::new(-1.0, 0.0)));
+        assert!((flipped.depth - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn apart_and_touching() {
+        assert!(collide(&square(0.0, 0.0, 4.0), &square(5.0, 0.0, 4.0)).is_none());
+        let touching = collide(&square(0.0, 0.0, 4.0), &square(4.0, 2.0, 4.0)).unwrap();
+        assert_eq!(touching.depth.to_bits(), 0.0_f32.to_bits());
+
+        let circle = Convex::from(Circle::new(Vec2::new(0.0, 0.0), 2.0));
+        assert!(collide(&circle, &Circle::new(Vec2::new(5.0, 0.0), 2.0).into()).is_none());
+        // Diagonally off a corner: the face axes overlap but the corner axis doesn't.
+        assert!(collide(
+            &square(0.0, 0.0, 4.0),
+            &Circle::new(Vec2::new(5.5, 5.5), 2.0).into()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn circles() {
+        let first = Convex::from(Circle::new(Vec2::new(0.0, 0.0), 2.0));
+        let contact = collide(&first, &Circle::new(Vec2::new(0.0, 3.0), 2.0).into()).unwrap();
+        assert!(close(contact.normal, Vec2::new(0.0, 1.0)));
+        assert!((contact.depth - 1.0).abs() < 1e-5);
+        assert!(close(contact.points[0], Vec2::new(0.0, 1.0)));
+
+        // On the same center any direction will do.
+        let same = collide(&first, &first).unwrap();
+        assert!((same.normal.len() - 1.0).abs() < 1e-5 && (same.depth - 4.0).abs() < 1e-5);
+
+        let against_face = collide(
+            &square(0.0, 0.0, 4.0),
+            &Circle::new(Vec2::new(2.0, 5.0), 2.0).into(),
+        )
+        .unwrap();
+        assert!(close(against_face.normal, Vec2::new(0.0, 1.0)));
+        assert!((against_face.depth - 1.0).abs() < 1e-5);
+        assert!(close(against_face.points[0], Vec2::new(2.0, 3.0)));
+        let from_circle = collide(
+            &Circle::new(Vec2::new(2.0, 5.0), 2.0).into(),
+            &square(0.0, 0.0, 4.0),
+        )
+        .unwrap();
+        assert!(close(from_circle.normal, Vec2::new(0.0, -1.0)));
+        assert!(close(from_circle.points[0], Vec2::new(2.0, 3.0)));
+    }
+
+    #[test]
+    fn collide_agrees_with_distance() {
+        let shapes = scatter(11, 60);
+        for first in &shapes {
+            for second in &shapes {
+                let apart = distance(&shape(first), &shape(second));
+                let Some(contact) = collide(first, second) else {
+                    assert!(apart > 0.0, "{first:?} {second:?}");
+                    continue;
+                };
+                assert!(apart < 1e-4, "{first:?} {second:?}: {apart}");
+                assert!((contact.normal.len() - 1.0).abs() < 1e-4);
+                assert!(contact.depth >= 0.0 && !contact.points.is_empty());
+                // Pushed out by the depth the shapes come apart, short of it they still overlap.
+                let out = second.translate(contact.normal * (contact.depth + 1e-2));
+                assert!(collide(first, &out).is_none(), "{first:?} {second:?}");
+                if contact.depth > 1e-2 {
+                    let short = second.translate(contact.normal * (contact.depth - 1e-2));
+                    assert!(collide(first, &short).is_some(), "{first:?} {second:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sweeping_boxes() {
+        let still = square(10.0, 0.0, 4.0);
+        let moving = square(0.0, 0.0, 4.0);
+        let impact = sweep(&moving, Vec2::new(12.0, 0.0), &still, Vec2::default()).unwrap();
+        assert!((impact.time - 0.5).abs() < 1e-5);
+        assert!(close(impact.normal, Vec2::new(1.0, 0.0)));
+        // Both moving covers the gap sooner.
+        let both = sweep(&moving, Vec2::new(12.0, 0.0), &still, Vec2::new(-12.0, 0.0)).unwrap();
+        assert!((both.time - 0.25).abs() < 1e-5);
+        // Too slow, or passing by.
+        assert!(sweep(&moving, Vec2::new(5.0, 0.0), &still, Vec2::default()).is_none());
+        assert!(sweep(
+            &moving,
+            Vec2::new(12.0, 0.0),
+            &square(10.0, 10.0, 4.0),
+            Vec2::default()
+        )
+        .is_none());
+        // Already overlapping.
+        let start = sweep(
+            &moving,
+            Vec2::new(1.0, 0.0),
+            &square(2.0, 0.0, 4.0),
+            Vec2::default(),
+        )
+        .unwrap();
+        assert_eq!(start.time.to_bits(), 0.0_f32.to_bits());
+    }
+
+    #[test]
+    fn sweeping_circles() {
+        let first = Convex::from(Circle::new(Vec2::new(0.0, 0.0), 1.0));
+        let second = Convex::from(Circle::new(Vec2::new(10.0, 0.0), 1.0));
+        let impact = sweep(&first, Vec2::new(16.0, 0.0), &second, Vec2::default()).unwrap();
+        assert!((impact.time - 0.5).abs() < 1e-5);
+        assert!(close(impact.normal, Vec2::new(1.0, 0.0)));
+
+        // A circle catching a box's corner.
+        let corners = [
+            Vec2::new(10.0, 0.5),
+            Vec2::new(14.0, 0.5),
+            Vec2::new(14.0, 4.5),
+            Vec2::new(10.0, 4.5),
+        ];
+        let corner = sweep(
+            &first,
+            Vec2::new(20.0, 0.0),
+            &Convex::polygon(&corners),
+            Vec2::default(),
+        )
+        .unwrap();
+        let center = Vec2::new(20.0 * corner.time, 0.0);
+        assert!((center.distance(corners[0]) - 1.0).abs() < 1e-4);
+        assert!(close(corner.normal, (corners[0] - center).normalize()));
+    }
+
+    #[test]
+    fn swept_shapes_end_up_touching() {
+        let shapes = scatter(23, 30);
+        let random = Random::new(5);
+        for first in &shapes {
+            for second in &shapes {
+                let velocity = Vec2::new(
+                    random.random_range(-12.0, 12.0),
+                    random.random_range(-12.0, 12.0),
+                );
+                let far = first.translate(-velocity);
+                let Some(impact) = sweep(&far, velocity, second, Vec2::default()) else {
+                    // Missing all the way means never touching along the path.
+                    for step in 0..=50 {
+                        let moved = far.translate(velocity * (step as f32 / 50.0));
+                        assert!(collide(&moved, second).is_none_or(|contact| contact.depth < 1e-3));
+                    }
+                    continue;
+                };
+                assert!((impact.normal.len() - 1.0).abs() < 1e-4);
+                let at = far.translate(velocity * impact.time);
+                let gap = distance(&shape(&at), &shape(second));
+                assert!(gap < 1e-2, "{far:?} {second:?} {impact:?}: {gap}");
+                if impact.time > 1e-2 {
+                    let before = far.translate(velocity * (impact.time - 1e-2));
+                    assert!(collide(&before, second).is_none_or(|contact| contact.depth < 1e-3));
+                }
+            }
+        }
+    }
+}
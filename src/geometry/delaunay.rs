@@ -0,0 +1,438 @@
+//! Delaunay triangulation (Bowyer-Watson) and the Voronoi diagram dual to it.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::geometry::polygon::Polygon;
+use crate::geometry::{Rect, Triangle};
+use crate::math::Vec2;
+use crate::predicates::{incircle, orient2d};
+
+/// Triangulation where no point lies inside any triangle's circumcircle.
+#[derive(Clone, Debug, Default)]
+pub struct Delaunay {
+    pub points: Vec<Vec2>,
+    /// Indices into `points`, counter-clockwise with y up.
+    pub triangles: Vec<[usize; 3]>,
+    /// The triangle across the edge opposite each corner, `None` on the hull.
+    pub neighbours: Vec<[Option<usize>; 3]>,
+    /// Points sharing an edge with each point, also filled for collinear points that make no triangles.
+    adjacent: Vec<Vec<usize>>,
+    /// Index of the first point at the same position, repeated points make no triangles of their own.
+    original: Vec<usize>,
+}
+
+impl Delaunay {
+    pub fn new(points: &[Vec2]) -> Self {
+        if points.is_empty() {
+            return Self::default();
+        }
+        let mut builder = Builder::new(points);
+        let original = (0..points.len())
+            .map(|index| builder.insert(index).unwrap_or(index))
+            .collect();
+        builder.finish(original)
+    }
+
+    pub fn triangle(&self, index: usize) -> Triangle {
+        let [a, b, c] = self.triangles[index];
+        Triangle::new(self.points[a], self.points[b], self.points[c])
+    }
+
+    pub fn to_triangles(&self) -> Vec<Triangle> {
+        (0..self.triangles.len())
+            .map(|index| self.triangle(index))
+            .collect()
+    }
+
+    /// Every edge once, as pairs of point indices.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        self.triangles
+            .iter()
+            .enumerate()
+            .flat_map(|(index, corners)| {
+                (0..3).filter_map(move |k| {
+                    let (from, to) = (corners[(k + 1) % 3], corners[(k + 2) % 3]);
+                    // Inner edges are seen from both sides, keep them from the lower triangle.
+                    let neighbour = self.neighbours[index][k];
+                    neighbour
+                        .is_none_or(|other| index < other)
+                        .then_some((from, to))
+                })
+            })
+            .collect()
+    }
+
+    #[allow(clippy::suboptimal_flops)]
+    pub fn circumcenter(&self, index: usize) -> Vec2 {
+        let [a, b, c] = self.triangles[index].map(|corner| self.points[corner]);
+        let (ab, ac) = (b - a, c - a);
+        let offset = Vec2::new(
+            ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+            ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+        ) / (2.0 * ab.cross(ac));
+        a + offset
+    }
+
+    /// Voronoi cell of every point in the order of `points`, clipped to `bounds`.
+    /// Each cell is the part of `bounds` closer to its point than to any other,
+    /// wound counter-clockwise (y up) like `Polygon::orient` winds outer rings.
+    pub fn voronoi(&self, bounds: &Rect) -> Vec<Polygon> {
+        let (top_left, top_right, bottom_right, bottom_left) = bounds.corners();
+        let frame = vec![top_left, top_right, bottom_right, bottom_left];
+        let cells: Vec<Vec<Vec2>> = (0..self.points.len())
+            .map(|index| {
+                if self.original[index] != index {
+                    return Vec::new();
+                }
+                self.adjacent[index]
+                    .iter()
+                    .fold(frame.clone(), |cell, other| {
+                        clip_closer(&cell, self.points[index], self.points[*other])
+                    })
+            })
+            .collect();
+        self.original
+            .iter()
+            .map(|original| Polygon::new(cells[*original].clone()))
+            .collect()
+    }
+}
+
+/// The part of the convex `cell` that is closer to `point` than to `other`.
+fn clip_closer(cell: &[Vec2], point: Vec2, other: Vec2) -> Vec<Vec2> {
+    let middle = (point + other) / 2.0;
+    let direction = other - point;
+    let side = |vertex: Vec2| (vertex - middle).dot(direction);
+    let mut clipped = Vec::with_capacity(cell.len() + 1);
+    for (i, &from) in cell.iter().enumerate() {
+        let to = cell[(i + 1) % cell.len()];
+        let (from_side, to_side) = (side(from), side(to));
+        if from_side <= 0.0 {
+            clipped.push(from);
+        }
+        if (from_side < 0.0 && to_side > 0.0) || (from_side > 0.0 && to_side < 0.0) {
+            clipped.push(from.lerp(to, from_side / (from_side - to_side)));
+        }
+    }
+    clipped
+}
+
+/// Triangulation in progress, it starts as one triangle around everything.
+struct Builder {
+    points: Vec<Vec2>,
+    triangles: Vec<[usize; 3]>,
+    neighbours: Vec<[Option<usize>; 3]>,
+    alive: Vec<bool>,
+    /// Where the search for the next point's triangle starts, recent triangles are usually close.
+    last: usize,
+}
+
+impl Builder {
+    /// The corners of the enclosing triangle come after the real points.
+    fn new(points: &[Vec2]) -> Self {
+        let bounds = Rect::bounding(points);
+        let size = Vec2::new(bounds.size.width as f32, bounds.size.height as f32);
+        let center = bounds.origin + size / 2.0;
+        let reach = size.x.max(size.y).max(1.0) * 1000.0;
+        let mut all = points.to_vec();
+        all.extend([
+            center + Vec2::new(-reach, -reach),
+            center + Vec2::new(reach, -reach),
+            center + Vec2::new(0.0, reach),
+        ]);
+        let count = points.len();
+        Self {
+            points: all,
+            triangles: vec![[count, count + 1, count + 2]],
+            neighbours: vec![[None; 3]],
+            alive: vec![true],
+            last: 0,
+        }
+    }
+
+    fn real_count(&self) -> usize {
+        self.points.len() - 3
+    }
+
+    /// Adds a point, or returns the index of an earlier point at the same position.
+    fn insert(&mut self, index: usize) -> Option<usize> {
+        let point = self.points[index];
+        let start = self.locate(point);
+        if let Some(&existing) = self.triangles[start]
+            .iter()
+            .find(|corner| self.points[**corner] == point)
+        {
+            return Some(existing);
+        }
+
+        // Triangles whose circumcircle holds the point form a connected cavity around it.
+        let mut cavity = vec![start];
+        let mut in_cavity = HashMap::from([(start, true)]);
+        let mut next = 0;
+        while next < cavity.len() {
+            let triangle = cavity[next];
+            next += 1;
+            for neighbour in self.neighbours[triangle].into_iter().flatten() {
+                if in_cavity.contains_key(&neighbour) {
+                    continue;
+                }
+                let [a, b, c] = self.triangles[neighbour].map(|corner| self.points[corner]);
+                let inside = incircle(a, b, c, point) == Ordering::Greater;
+                in_cavity.insert(neighbour, inside);
+                if inside {
+                    cavity.push(neighbour);
+                }
+            }
+        }
+
+        // Fan from the point to every edge on the cavity's boundary.
+        let mut by_start = HashMap::new();
+        let mut by_end = HashMap::new();
+        for &triangle in &cavity {
+            self.alive[triangle] = false;
+            for k in 0..3 {
+                let outside = self.neighbours[triangle][k];
+                if outside.is_some_and(|other| in_cavity.get(&other) == Some(&true)) {
+                    continue;
+                }
+                let corners = self.triangles[triangle];
+                let (from, to) = (corners[(k + 1) % 3], corners[(k + 2) % 3]);
+                let created = self.triangles.len();
+                self.triangles.push([from, to, index]);
+                self.neighbours.push([None, None, outside]);
+                self.alive.push(true);
+                if let Some(other) = outside {
+                    let slot = self.neighbours[other]
+                        .iter()
+                        .position(|neighbour| *neighbour == Some(triangle))
+                        .expect("neighbours point at each other");
+                    self.neighbours[other][slot] = Some(created);
+                }
+                by_start.insert(from, created);
+                by_end.insert(to, created);
+            }
+        }
+        for (&from, &created) in &by_start {
+            let to = self.triangles[created][1];
+            self.neighbours[created][0] = by_start.get(&to).copied();
+            self.neighbours[created][1] = by_end.get(&from).copied();
+        }
+        self.last = self.triangles.len() - 1;
+        None
+    }
+
+    /// Triangle containing the point, walking towards it from the last created triangle.
+    fn locate(&self, point: Vec2) -> usize {
+        let mut current = self.last;
+        for _ in 0..self.triangles.len() {
+            let corners = self.triangles[current];
+            let across = (0..3).find(|k| {
+                let (from, to) = (corners[(k + 1) % 3], corners[(k + 2) % 3]);
+                orient2d(self.points[from], self.points[to], point) == Ordering::Less
+            });
+            match across.and_then(|k| self.neighbours[current][k]) {
+                Some(next) => current = next,
+                None => return current,
+            }
+        }
+        // The walk can't loop on a Delaunay triangulation, this only guards against bugs.
+        (0..self.triangles.len())
+            .find(|index| {
+                self.alive[*index] && {
+                    let [a, b, c] = self.triangles[*index].map(|corner| self.points[corner]);
+                    orient2d(a, b, point) != Ordering::Less
+                        && orient2d(b, c, point) != Ordering::Less
+                        && orient2d(c, a, point) != Ordering::Less
+                }
+            })
+            .unwrap_or(self.last)
+    }
+
+    /// Drops the triangles touching the enclosing corners and renumbers the rest.
+    fn finish(mut self, original: Vec<usize>) -> Delaunay {
+        let count = self.real_count();
+        let mut adjacent = vec![Vec::new(); count];
+        let mut renumbered = vec![None; self.triangles.len()];
+        let mut triangles = Vec::new();
+        for (index, corners) in self.triangles.iter().enumerate() {
+            if !self.alive[index] {
+                continue;
+            }
+            // Edges between real points count even in triangles that get dropped,
+            // that keeps the neighbours of collinear points.
+            for k in 0..3 {
+                let (from, to) = (corners[k], corners[(k + 1) % 3]);
+                if from < count && to < count {
+                    adjacent[from].push(to);
+                    adjacent[to].push(from);
+                }
+            }
+            if corners.iter().all(|corner| *corner < count) {
+                renumbered[index] = Some(triangles.len());
+                triangles.push(*corners);
+            }
+        }
+        let neighbours = (0..self.triangles.len())
+            .filter(|index| renumbered[*index].is_some())
+            .map(|index| self.neighbours[index].map(|other| other.and_then(|o| renumbered[o])))
+            .collect();
+        for list in &mut adjacent {
+            list.sort_unstable();
+            list.dedup();
+        }
+        self.points.truncate(count);
+        Delaunay {
+            points: self.points,
+            triangles,
+            neighbours,
+            adjacent,
+            original,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::hull::convex_hull;
+    use crate::geometry::polygon::{ring_signed_area, FillRule};
+    use crate::geometry::Size;
+    use crate::random::Random;
+
+    fn scatter(seed: u64, count: usize) -> Vec<Vec2> {
+        let random = Random::new(seed);
+        (0..count)
+            .map(|_| {
+                Vec2::new(
+                    random.random_range(0.0, 100.0),
+                    random.random_range(0.0, 80.0),
+                )
+            })
+            .collect()
+    }
+
+    /// Counter-clockwise triangles with empty circumcircles that tile the hull, and
+    /// neighbours that point back at each other across a shared edge.
+    fn check(delaunay: &Delaunay) {
+        let mut area = 0.0;
+        for (index, [a, b, c]) in delaunay.triangles.iter().enumerate() {
+            let [pa, pb, pc] = [*a, *b, *c].map(|corner| delaunay.points[corner]);
+            assert_eq!(orient2d(pa, pb, pc), Ordering::Greater);
+            area += ring_signed_area(&[pa, pb, pc]);
+            for point in &delaunay.points {
+                assert_ne!(incircle(pa, pb, pc, *point), Ordering::Greater, "{index}");
+            }
+            for k in 0..3 {
+                let Some(other) = delaunay.neighbours[index][k] else {
+                    continue;
+                };
+                let corners = delaunay.triangles[index];
+                let edge = [corners[(k + 1) % 3], corners[(k + 2) % 3]];
+                assert!(edge
+                    .iter()
+                    .all(|corner| delaunay.triangles[other].contains(corner)));
+                assert!(delaunay.neighbours[other].contains(&Some(index)));
+            }
+        }
+        let hull = ring_signed_area(&convex_hull(&delaunay.points));
+        assert!((area - hull).abs() < hull * 1e-5, "{area} {hull}");
+    }
+
+    #[test]
+    fn random_points() {
+        for seed in 0..10 {
+            let points = scatter(seed, 60);
+            let delaunay = Delaunay::new(&points);
+            check(&delaunay);
+            // Euler's formula for points in general position.
+            let hull = convex_hull(&points).len();
+            assert_eq!(delaunay.triangles.len(), 2 * points.len() - 2 - hull);
+            assert_eq!(delaunay.edges().len(), 3 * points.len() - 3 - hull);
+        }
+    }
+
+    #[test]
+    fn grid_points_are_cocircular() {
+        let points: Vec<Vec2> = (0..36)
+            .map(|i| Vec2::new((i % 6) as f32 * 10.0, (i / 6) as f32 * 10.0))
+            .collect();
+        let delaunay = Delaunay::new(&points);
+        check(&delaunay);
+        assert_eq!(delaunay.triangles.len(), 2 * 5 * 5);
+    }
+
+    #[test]
+    fn repeated_and_collinear_points() {
+        let mut points = scatter(3, 10);
+        points.push(points[4]);
+        let delaunay = Delaunay::new(&points);
+        check(&delaunay);
+        assert!(delaunay
+            .triangles
+            .iter()
+            .flatten()
+            .all(|corner| *corner != 10));
+
+        let line: Vec<Vec2> = (0..5).map(|i| Vec2::new(i as f32 * 10.0, 5.0)).collect();
+        let flat = Delaunay::new(&line);
+        assert!(flat.triangles.is_empty());
+        assert!(Delaunay::new(&[]).triangles.is_empty());
+    }
+
+    #[test]
+    fn circumcenter_is_equally_far_from_the_corners() {
+        let delaunay = Delaunay::new(&scatter(1, 20));
+        for (index, corners) in delaunay.triangles.iter().enumerate() {
+            let center = delaunay.circumcenter(index);
+            let [a, b, c] = corners.map(|corner| delaunay.points[corner].distance(center));
+            assert!((a - b).abs() < 1e-2 && (a - c).abs() < 1e-2, "{a} {b} {c}");
+        }
+    }
+
+    #[test]
+    fn voronoi_cells_hold_the_closest_points() {
+        let bounds = Rect::new(Vec2::new(0.0, 0.0), Size::new(100, 80));
+        let mut points = scatter(2, 25);
+        points.push(points[0]);
+        let cells = Delaunay::new(&points).voronoi(&bounds);
+        assert_eq!(cells.len(), points.len());
+        assert_eq!(cells[25], cells[0]);
+
+        let area: f32 = cells[..25].iter().map(Polygon::area).sum();
+        assert!((area - 8000.0).abs() < 0.1, "{area}");
+        for cell in &cells {
+            assert!(cell.signed_area() > 0.0);
+        }
+        let random = Random::new(9);
+        for _ in 0..500 {
+            let sample = Vec2::new(
+                random.random_range(0.0, 100.0),
+                random.random_range(0.0, 80.0),
+            );
+            let closest = (0..25)
+                .min_by(|a, b| {
+                    let a = points[*a].distance_squared(sample);
+                    let b = points[*b].distance_squared(sample);
+                    a.total_cmp(&b)
+                })
+                .expect("there are points");
+            assert!(
+                cells[closest].contains(sample, FillRule::NonZero),
+                "{sample:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn voronoi_of_collinear_points_are_strips() {
+        let bounds = Rect::new(Vec2::new(0.0, 0.0), Size::new(40, 10));
+        let line: Vec<Vec2> = (0..4)
+            .map(|i| Vec2::new((i as f32).mul_add(10.0, 5.0), 5.0))
+            .collect();
+        let cells = Delaunay::new(&line).voronoi(&bounds);
+        for cell in cells {
+            assert!((cell.area() - 100.0).abs() < 1e-3, "{}", cell.area());
+        }
+    }
+}
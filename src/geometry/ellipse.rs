@@ -0,0 +1,592 @@
+//! Circles, ellipses and arcs as pixels. Axis-aligned shapes use the midpoint algorithms,
+//! centers and radii are rounded to whole pixels for them. Rotated ellipses are filled by
+//! solving each scanline exactly and outlined along the edge of the fill.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::geometry::{Circle, Rect};
+use crate::graphics::line;
+use crate::math::Vec2;
+
+impl Circle {
+    pub fn solid_color(&self) -> Vec<Vec2> {
+        let center = self.center.round();
+        Raster::from_outline(center, midpoint_circle(self.radius)).solid()
+    }
+
+    pub fn empty(&self) -> Vec<Vec2> {
+        let center = self.center.round();
+        Raster::from_outline(center, midpoint_circle(self.radius)).outline
+    }
+}
+
+/// Ellipse with semi-axes `radii`, turned by `angle` around its center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ellipse {
+    pub center: Vec2,
+    pub radii: Vec2,
+    pub angle: f32,
+}
+
+impl From<Circle> for Ellipse {
+    fn from(circle: Circle) -> Self {
+        Self::new(circle.center, Vec2::new(circle.radius, circle.radius))
+    }
+}
+
+impl Ellipse {
+    pub fn new(center: Vec2, radii: Vec2) -> Self {
+        Self {
+            center,
+            radii,
+            angle: 0.0,
+        }
+    }
+
+    pub fn angle(self, angle: f32) -> Self {
+        Self { angle, ..self }
+    }
+
+    /// Point on the ellipse at parameter `t`, which goes the same way as `Vec2::from_angle`.
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        let (sin, cos) = t.sin_cos();
+        self.center + Vec2::new(self.radii.x * cos, self.radii.y * sin).rotate(self.angle)
+    }
+
+    /// Parameter of the ellipse point in the direction of `point` as seen from the center, in (-PI, PI].
+    pub fn parameter_of(&self, point: Vec2) -> f32 {
+        parameter(self.center, self.radii, self.angle, point)
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let local = (point - self.center).rotate(-self.angle);
+        let scaled = Vec2::new(
+            local.x / self.radii.x.abs().max(f32::MIN_POSITIVE),
+            local.y / self.radii.y.abs().max(f32::MIN_POSITIVE),
+        );
+        scaled.length_squared() <= 1.0
+    }
+
+    pub fn bounding(&self) -> Rect {
+        let extent = self.extent();
+        Rect::bounding(&[self.center - extent, self.center + extent])
+    }
+
+    pub fn arc(self, start: f32, end: f32) -> Arc {
+        Arc::new(self, start, end)
+    }
+
+    pub fn solid_color(&self) -> Vec<Vec2> {
+        self.raster().solid()
+    }
+
+    pub fn empty(&self) -> Vec<Vec2> {
+        self.raster().outline
+    }
+
+    /// Half the size of the axis-aligned box around the ellipse.
+    #[allow(clippy::suboptimal_flops)]
+    fn extent(&self) -> Vec2 {
+        let (sin, cos) = self.angle.sin_cos();
+        let Vec2 { x: a, y: b } = self.radii;
+        Vec2::new(
+            (a * a * cos * cos + b * b * sin * sin).sqrt(),
+            (a * a * sin * sin + b * b * cos * cos).sqrt(),
+        )
+    }
+
+    fn raster(&self) -> Raster {
+        let center = self.center.round();
+        // Squared like `scanlines` inverts them, so radii too small for that count as zero.
+        let squared = self.radii * self.radii;
+        if squared.x == 0.0 || squared.y == 0.0 {
+            // Flat, only the segment along the other radius is left, or just the center.
+            let reach = self.radii.rotate(self.angle);
+            let (from, to) = (self.center - reach, self.center + reach);
+            let offsets = line(from, to)
+                .into_iter()
+                .chain([to.round()])
+                .map(|point| ((point.x - center.x) as i32, (point.y - center.y) as i32))
+                .collect();
+            return Raster::from_outline(center, offsets);
+        }
+        let (sin, cos) = self.angle.sin_cos();
+        let quarter_turns = self.angle / FRAC_PI_2;
+        let axis_aligned = (quarter_turns - quarter_turns.round()).abs() < 1e-6;
+        let round = (self.radii.x.abs() - self.radii.y.abs()).abs() < 1e-6;
+        if round || axis_aligned {
+            // Turned by a multiple of a quarter, only the radii swap.
+            let radii = if sin.abs() > cos.abs() {
+                Vec2::new(self.radii.y, self.radii.x)
+            } else {
+                self.radii
+            };
+            return Raster::from_outline(center, midpoint_ellipse(radii.x, radii.y));
+        }
+        self.scanlines()
+    }
+
+    /// Rows of the rotated ellipse, `dx` solves `A dx² + B dx dy + C dy² = 1` on each of them.
+    #[allow(clippy::suboptimal_flops)]
+    fn scanlines(&self) -> Raster {
+        let (sin, cos) = self.angle.sin_cos();
+        let inverse_a = 1.0 / (self.radii.x * self.radii.x);
+        let inverse_b = 1.0 / (self.radii.y * self.radii.y);
+        let a = cos * cos * inverse_a + sin * sin * inverse_b;
+        let b = 2.0 * cos * sin * (inverse_a - inverse_b);
+        let c = sin * sin * inverse_a + cos * cos * inverse_b;
+
+        let Vec2 { x: cx, y: cy } = self.center;
+        let extent = self.extent().y;
+        let mut rows = Vec::new();
+        for y in (cy - extent).ceil() as i32..=(cy + extent).floor() as i32 {
+            let dy = y as f32 - cy;
+            let discriminant = (b * dy).powi(2) - 4.0 * a * (c * dy * dy - 1.0);
+            if discriminant < 0.0 {
+                continue;
+            }
+            let root = discriminant.sqrt();
+            let (low, high) = ((-b * dy - root) / (2.0 * a), (-b * dy + root) / (2.0 * a));
+            let (left, right) = ((cx + low).ceil() as i32, (cx + high).floor() as i32);
+            if left <= right {
+                rows.push((y, left, right));
+            } else {
+                // Thinner than a pixel here, the closest one keeps the shape connected.
+                let middle = (cx + f32::midpoint(low, high)).round() as i32;
+                rows.push((y, middle, middle));
+            }
+        }
+        if rows.is_empty() {
+            let center = self.center.round();
+            rows.push((center.y as i32, center.x as i32, center.x as i32));
+        }
+        Raster::from_rows(self.center, rows)
+    }
+}
+
+/// What an arc's outline and fill include besides the curve.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ArcKind {
+    /// Only the curve is outlined, filled like `Pie`.
+    #[default]
+    Open,
+    /// Closed by a straight line between the ends.
+    Chord,
+    /// Closed by lines from both ends to the center.
+    Pie,
+}
+
+/// Part of an ellipse from `start` to `end`, parameters as in `Ellipse::point_at`.
+/// It goes towards growing parameters, clockwise on screen, and covers the whole ellipse
+/// once `end` is a full turn past `start`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arc {
+    pub ellipse: Ellipse,
+    pub start: f32,
+    pub end: f32,
+    pub kind: ArcKind,
+}
+
+impl Arc {
+    pub fn new(ellipse: Ellipse, start: f32, end: f32) -> Self {
+        Self {
+            ellipse,
+            start,
+            end,
+            kind: ArcKind::Open,
+        }
+    }
+
+    pub fn kind(self, kind: ArcKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    /// How far the arc goes from `start`, in [0, TAU].
+    pub fn sweep(&self) -> f32 {
+        let sweep = self.end - self.start;
+        if sweep >= TAU {
+            TAU
+        } else {
+            sweep.rem_euclid(TAU)
+        }
+    }
+
+    pub fn contains_parameter(&self, t: f32) -> bool {
+        (t - self.start).rem_euclid(TAU) <= self.sweep()
+    }
+
+    pub fn solid_color(&self) -> Vec<Vec2> {
+        let raster = self.ellipse.raster();
+        let center = raster.center;
+        let full = self.sweep() >= TAU;
+        let (from, to) = self.ends(center);
+        let arc_side = (to - from).cross(self.middle(center) - from);
+        raster
+            .solid()
+            .into_iter()
+            .filter(|point| {
+                full || match self.kind {
+                    ArcKind::Open | ArcKind::Pie => {
+                        *point == center || self.contains_parameter(self.parameter(center, *point))
+                    }
+                    ArcKind::Chord => (to - from).cross(*point - from) * arc_side >= 0.0,
+                }
+            })
+            .collect()
+    }
+
+    pub fn empty(&self) -> Vec<Vec2> {
+        let raster = self.ellipse.raster();
+        let center = raster.center;
+        let mut points: Vec<Vec2> = raster
+            .outline
+            .into_iter()
+            .filter(|point| self.contains_parameter(self.parameter(center, *point)))
+            .collect();
+        if self.sweep() >= TAU {
+            return points;
+        }
+        let (from, to) = self.ends(center);
+        let (from, to) = (from.round(), to.round());
+        match self.kind {
+            ArcKind::Open => {}
+            ArcKind::Chord => points.extend(line(from, to)),
+            ArcKind::Pie => {
+                points.extend(line(center.round(), from));
+                points.extend(line(center.round(), to));
+            }
+        }
+        points
+    }
+
+    fn parameter(&self, center: Vec2, point: Vec2) -> f32 {
+        parameter(center, self.ellipse.radii, self.ellipse.angle, point)
+    }
+
+    /// Both ends around the center actually drawn.
+    fn ends(&self, center: Vec2) -> (Vec2, Vec2) {
+        let ellipse = Ellipse {
+            center,
+            ..self.ellipse
+        };
+        (ellipse.point_at(self.start), ellipse.point_at(self.end))
+    }
+
+    fn middle(&self, center: Vec2) -> Vec2 {
+        let ellipse = Ellipse {
+            center,
+            ..self.ellipse
+        };
+        ellipse.point_at(self.start + self.sweep() / 2.0)
+    }
+}
+
+fn parameter(center: Vec2, radii: Vec2, angle: f32, point: Vec2) -> f32 {
+    let local = (point - center).rotate(-angle);
+    Vec2::new(
+        local.x / radii.x.abs().max(f32::MIN_POSITIVE),
+        local.y / radii.y.abs().max(f32::MIN_POSITIVE),
+    )
+    .angle()
+}
+
+/// Outline pixels and the horizontal spans covered by the shape, both around `center`.
+struct Raster {
+    center: Vec2,
+    outline: Vec<Vec2>,
+    /// Row, then the first and last column of the span.
+    rows: Vec<(i32, i32, i32)>,
+}
+
+impl Raster {
+    /// Rows are filled between the outline's leftmost and rightmost pixel, which suits shapes
+    /// that are convex and symmetric like the midpoint algorithms draw them.
+    fn from_outline(center: Vec2, mut offsets: Vec<(i32, i32)>) -> Self {
+        offsets.sort_unstable_by_key(|(x, y)| (*y, *x));
+        offsets.dedup();
+        let mut rows: Vec<(i32, i32, i32)> = Vec::new();
+        for &(x, y) in &offsets {
+            match rows.last_mut() {
+                Some((row, _, right)) if *row == y => *right = x,
+                _ => rows.push((y, x, x)),
+            }
+        }
+        let (cx, cy) = (center.x as i32, center.y as i32);
+        Self {
+            center,
+            outline: offsets
+                .into_iter()
+                .map(|(x, y)| Vec2::new((cx + x) as f32, (cy + y) as f32))
+                .collect(),
+            rows: rows
+                .into_iter()
+                .map(|(y, left, right)| (cy + y, cx + left, cx + right))
+                .collect(),
+        }
+    }
+
+    /// Rows must come one after another, the outline is every pixel of the fill
+    /// with a side neighbour outside of it.
+    fn from_rows(center: Vec2, rows: Vec<(i32, i32, i32)>) -> Self {
+        let covers = |index: Option<usize>, x: i32| {
+            index
+                .and_then(|index| rows.get(index))
+                .is_some_and(|(_, left, right)| (*left..=*right).contains(&x))
+        };
+        let mut outline = Vec::new();
+        for (index, &(y, left, right)) in rows.iter().enumerate() {
+            for x in left..=right {
+                if x == left
+                    || x == right
+                    || !covers(index.checked_sub(1), x)
+                    || !covers(Some(index + 1), x)
+                {
+                    outline.push(Vec2::new(x as f32, y as f32));
+                }
+            }
+        }
+        Self {
+            center,
+            outline,
+            rows,
+        }
+    }
+
+    fn solid(&self) -> Vec<Vec2> {
+        self.rows
+            .iter()
+            .flat_map(|&(y, left, right)| {
+                (left..=right).map(move |x| Vec2::new(x as f32, y as f32))
+            })
+            .collect()
+    }
+}
+
+/// Pixel offsets of a circle from its center, one octant mirrored eight ways.
+fn midpoint_circle(radius: f32) -> Vec<(i32, i32)> {
+    let radius = radius.abs().round() as i32;
+    let (mut x, mut y) = (radius, 0);
+    let mut decision = 1 - radius;
+    let mut offsets = Vec::with_capacity(8 * radius as usize + 1);
+    while x >= y {
+        for (dx, dy) in [(x, y), (y, x)] {
+            offsets.extend([(dx, dy), (-dx, dy), (dx, -dy), (-dx, -dy)]);
+        }
+        y += 1;
+        if decision < 0 {
+            decision += 2 * y + 1;
+        } else {
+            x -= 1;
+            decision += 2 * (y - x) + 1;
+        }
+    }
+    offsets
+}
+
+/// Pixel offsets of an axis-aligned ellipse from its center. The first region steps along x
+/// while the curve is flatter than 45 degrees, the second along y. Decisions are kept four
+/// times larger so they stay whole numbers.
+fn midpoint_ellipse(radius_x: f32, radius_y: f32) -> Vec<(i32, i32)> {
+    let (a, b) = (
+        i64::from(radius_x.abs().round() as i32),
+        i64::from(radius_y.abs().round() as i32),
+    );
+    if b == 0 {
+        return (-a..=a).map(|x| (x as i32, 0)).collect();
+    }
+    let (a2, b2) = (a * a, b * b);
+    let mut offsets = Vec::new();
+    let mut mirror = |x: i64, y: i64| {
+        let (x, y) = (x as i32, y as i32);
+        offsets.extend([(x, y), (-x, y), (x, -y), (-x, -y)]);
+    };
+
+    let (mut x, mut y) = (0, b);
+    let (mut step_x, mut step_y) = (0, 2 * a2 * y);
+    let mut decision = 4 * b2 - 4 * a2 * b + a2;
+    while step_x < step_y {
+        mirror(x, y);
+        x += 1;
+        step_x += 2 * b2;
+        if decision < 0 {
+            decision += 4 * (step_x + b2);
+        } else {
+            y -= 1;
+            step_y -= 2 * a2;
+            decision += 4 * (step_x - step_y + b2);
+        }
+    }
+
+    let mut decision = b2 * (2 * x + 1).pow(2) + 4 * a2 * (y - 1).pow(2) - 4 * a2 * b2;
+    while y >= 0 {
+        mirror(x, y);
+        y -= 1;
+        step_y -= 2 * a2;
+        if decision > 0 {
+            decision += 4 * (a2 - step_y);
+        } else {
+            x += 1;
+            step_x += 2 * b2;
+            decision += 4 * (step_x - step_y + a2);
+        }
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn set(points: &[Vec2]) -> HashSet<(i32, i32)> {
+        points.iter().map(|p| (p.x as i32, p.y as i32)).collect()
+    }
+
+    /// Every pixel touches another one, diagonals included.
+    fn connected(points: &[Vec2]) -> bool {
+        let pixels = set(points);
+        pixels.iter().all(|(x, y)| {
+            (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+                .any(|(dx, dy)| (dx, dy) != (0, 0) && pixels.contains(&(x + dx, y + dy)))
+        })
+    }
+
+    #[test]
+    fn midpoint_circle_stays_on_the_radius() {
+        for radius in [1.0, 2.0, 7.0, 20.0] {
+            let circle = Circle::new(Vec2::new(30.0, 40.0), radius);
+            let outline = circle.empty();
+            assert!(connected(&outline), "{radius}");
+            for point in &outline {
+                let distance = point.distance(circle.center);
+                assert!((distance - radius).abs() < 0.75, "{radius} {point:?}");
+            }
+            let solid = circle.solid_color();
+            assert_eq!(set(&solid).len(), solid.len());
+            assert!(set(&outline).is_subset(&set(&solid)));
+            let area = PI * radius * radius;
+            assert!(
+                (solid.len() as f32 - area).abs() <= TAU * radius,
+                "{radius}"
+            );
+        }
+    }
+
+    #[test]
+    fn midpoint_ellipse_stays_on_the_curve() {
+        let ellipse = Ellipse::new(Vec2::new(0.0, 0.0), Vec2::new(15.0, 6.0));
+        let outline = ellipse.empty();
+        assert!(connected(&outline));
+        for point in &outline {
+            // Within a pixel, measured along the direction from the center.
+            let on_curve = ellipse.point_at(ellipse.parameter_of(*point));
+            assert!(point.len() - on_curve.len() < 1.0, "{point:?}");
+            assert!(on_curve.len() - point.len() < 1.0, "{point:?}");
+        }
+        let solid = set(&ellipse.solid_color());
+        for (x, y) in &solid {
+            assert!(solid.contains(&(-x, *y)) && solid.contains(&(*x, -y)));
+        }
+        // Turning by a quarter only swaps the radii.
+        let turned = ellipse.angle(FRAC_PI_2);
+        let swapped = Ellipse::new(Vec2::new(0.0, 0.0), Vec2::new(6.0, 15.0));
+        assert_eq!(set(&turned.solid_color()), set(&swapped.solid_color()));
+    }
+
+    #[test]
+    fn rotated_ellipse_fills_its_inside() {
+        let ellipse = Ellipse::new(Vec2::new(20.3, 10.6), Vec2::new(12.0, 4.0)).angle(0.6);
+        let solid = ellipse.solid_color();
+        assert!(solid.iter().all(|point| ellipse.contains(*point)));
+        // Every inside pixel is there.
+        let extent = ellipse.extent();
+        let mut inside = 0;
+        for y in (ellipse.center.y - extent.y) as i32 - 1..=(ellipse.center.y + extent.y) as i32 + 1
+        {
+            for x in
+                (ellipse.center.x - extent.x) as i32 - 1..=(ellipse.center.x + extent.x) as i32 + 1
+            {
+                inside += i32::from(ellipse.contains(Vec2::new(x as f32, y as f32)));
+            }
+        }
+        assert_eq!(solid.len(), inside as usize);
+        let outline = ellipse.empty();
+        assert!(connected(&outline));
+        assert!(set(&outline).is_subset(&set(&solid)));
+    }
+
+    #[test]
+    fn extent_holds_the_turned_ellipse() {
+        let ellipse = Ellipse::new(Vec2::new(0.0, 0.0), Vec2::new(9.0, 3.0)).angle(1.0);
+        let extent = ellipse.extent();
+        let (mut max_x, mut max_y) = (0.0_f32, 0.0_f32);
+        for step in 0..3600 {
+            let point = ellipse.point_at(step as f32 * TAU / 3600.0);
+            max_x = max_x.max(point.x.abs());
+            max_y = max_y.max(point.y.abs());
+        }
+        assert!((extent.x - max_x).abs() < 1e-3 && (extent.y - max_y).abs() < 1e-3);
+        for t in [0.0, 1.0, -2.5, 3.0] {
+            let point = ellipse.point_at(t);
+            assert!((ellipse.parameter_of(point) - t).abs() < 1e-4, "{t}");
+        }
+    }
+
+    #[test]
+    fn zero_radii() {
+        let center = Vec2::new(5.0, 5.0);
+        assert_eq!(Circle::new(center, 0.0).solid_color(), [center]);
+        assert_eq!(
+            Ellipse::new(center, Vec2::new(0.0, 0.0)).solid_color(),
+            [center]
+        );
+        let flat = Ellipse::new(center, Vec2::new(3.0, 0.0));
+        assert_eq!(set(&flat.solid_color()), (2..=8).map(|x| (x, 5)).collect());
+        // Turned flat ellipses are a line through the center.
+        let diagonal = Ellipse::new(center, Vec2::new(3.0, 0.0)).angle(PI / 4.0);
+        let pixels = diagonal.empty();
+        assert!(connected(&pixels) && pixels.len() >= 5);
+        assert!(pixels.iter().all(|p| (p.x - p.y).abs() <= 1.0));
+        // Too small to square, still drawn as a point.
+        let tiny = Ellipse::new(center, Vec2::new(1e-30, 2.0)).angle(0.3);
+        assert!(!tiny.solid_color().is_empty());
+    }
+
+    #[test]
+    fn arc_sweeps() {
+        let ellipse = Ellipse::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+        assert!((ellipse.arc(0.0, FRAC_PI_2).sweep() - FRAC_PI_2).abs() < 1e-6);
+        assert!((ellipse.arc(3.0 * FRAC_PI_2, 0.0).sweep() - FRAC_PI_2).abs() < 1e-6);
+        assert!((ellipse.arc(1.0, 3.0 * TAU).sweep() - TAU).abs() < 1e-6);
+        let arc = ellipse.arc(-0.5, 0.5);
+        assert!(arc.contains_parameter(0.0) && arc.contains_parameter(TAU + 0.1));
+        assert!(!arc.contains_parameter(PI));
+    }
+
+    #[test]
+    fn pie_and_chord_fill() {
+        let ellipse = Ellipse::new(Vec2::new(0.0, 0.0), Vec2::new(20.0, 20.0));
+        let whole = ellipse.solid_color().len() as f32;
+        let pie = ellipse.arc(0.0, FRAC_PI_2).kind(ArcKind::Pie).solid_color();
+        assert!(pie.iter().all(|p| p.x >= 0.0 && p.y >= 0.0));
+        assert!(
+            (pie.len() as f32 - whole / 4.0).abs() < 30.0,
+            "{}",
+            pie.len()
+        );
+
+        let chord = ellipse.arc(0.0, PI).kind(ArcKind::Chord).solid_color();
+        assert!(chord.iter().all(|p| p.y >= 0.0));
+        let full = ellipse.arc(0.0, TAU).solid_color();
+        assert_eq!(full.len(), whole as usize);
+
+        let outline = ellipse.arc(0.0, FRAC_PI_2).kind(ArcKind::Pie).empty();
+        assert!(outline.contains(&Vec2::new(0.0, 0.0)));
+        assert!(connected(&outline));
+        let open = ellipse.arc(0.0, FRAC_PI_2).empty();
+        assert!(!open.contains(&Vec2::new(0.0, 0.0)));
+    }
+}
@@ -0,0 +1,343 @@
+//! Enclosing shapes of point sets: convex hull, smallest circle, smallest rotated rectangle and diameter.
+
+use std::cmp::Ordering;
+
+use crate::geometry::{Circle, OrientedRect};
+use crate::math::Vec2;
+use crate::predicates::orient2d;
+
+/// Corners of the convex hull counter-clockwise (y up), starting from the leftmost point
+/// (Andrew's monotone chain). Points on the hull's edges are left out.
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() + 1);
+    let push = |hull: &mut Vec<Vec2>, chain_start: usize, point: Vec2| {
+        while hull.len() >= chain_start + 2
+            && orient2d(hull[hull.len() - 2], hull[hull.len() - 1], point) != Ordering::Greater
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    };
+    // Lower chain left to right, then the upper chain back to the start.
+    for &point in &sorted {
+        push(&mut hull, 0, point);
+    }
+    let upper_start = hull.len() - 1;
+    for &point in sorted.iter().rev().skip(1) {
+        push(&mut hull, upper_start, point);
+    }
+    // The upper chain ends on the first point again.
+    hull.pop();
+    hull
+}
+
+/// Smallest circle containing every point (Welzl's algorithm), `None` without points.
+pub fn min_enclosing_circle(points: &[Vec2]) -> Option<Circle> {
+    // Only the hull can touch the circle, and a fixed shuffle keeps the expected time linear.
+    let mut points = convex_hull(points);
+    fastrand::Rng::with_seed(points.len() as u64).shuffle(&mut points);
+
+    let mut circle = Circle::new(*points.first()?, 0.0);
+    for i in 1..points.len() {
+        if encloses(&circle, points[i]) {
+            continue;
+        }
+        circle = Circle::new(points[i], 0.0);
+        for j in 0..i {
+            if encloses(&circle, points[j]) {
+                continue;
+            }
+            circle = diameter_circle(points[i], points[j]);
+            for k in 0..j {
+                if !encloses(&circle, points[k]) {
+                    circle = circumcircle(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    Some(circle)
+}
+
+/// Allows for the rounding in the circle's construction.
+#[allow(clippy::suboptimal_flops)]
+fn encloses(circle: &Circle, point: Vec2) -> bool {
+    circle.center.distance(point) <= circle.radius * (1.0 + 1e-5) + 1e-5
+}
+
+fn diameter_circle(a: Vec2, b: Vec2) -> Circle {
+    Circle::new((a + b) / 2.0, a.distance(b) / 2.0)
+}
+
+/// Circle through all three points, collinear points get the circle over the furthest two.
+#[allow(clippy::suboptimal_flops)]
+fn circumcircle(a: Vec2, b: Vec2, c: Vec2) -> Circle {
+    let (ab, ac) = (b - a, c - a);
+    let denominator = 2.0 * ab.cross(ac);
+    if orient2d(a, b, c) == Ordering::Equal || denominator == 0.0 {
+        let pairs = [(a, b), (a, c), (b, c)];
+        let (from, to) = pairs
+            .into_iter()
+            .max_by(|x, y| {
+                x.0.distance_squared(x.1)
+                    .total_cmp(&y.0.distance_squared(y.1))
+            })
+            .expect("there are three pairs");
+        return diameter_circle(from, to);
+    }
+    let offset = Vec2::new(
+        ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+        ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+    ) / denominator;
+    Circle::new(a + offset, offset.len())
+}
+
+/// Rotated rectangle of least area around the points. One of its sides lies on a hull edge,
+/// rotating calipers walk all of them in linear time. `None` without points.
+pub fn min_area_rect(points: &[Vec2]) -> Option<OrientedRect> {
+    let hull = convex_hull(points);
+    match hull[..] {
+        [] => return None,
+        [point] => return Some(OrientedRect::new(point, Vec2::default(), 0.0)),
+        [a, b] => {
+            let half = (b - a) / 2.0;
+            return Some(OrientedRect::new(
+                a + half,
+                Vec2::new(half.len(), 0.0),
+                half.angle(),
+            ));
+        }
+        _ => {}
+    }
+
+    let count = hull.len();
+    let at = |i: usize| hull[i % count];
+    // Furthest along the edge, furthest away from it and furthest back along it.
+    let (mut right, mut top, mut left) = (1, 1, 1);
+    let mut best: Option<(f32, OrientedRect)> = None;
+    for i in 0..count {
+        let along = (at(i + 1) - at(i)).normalize();
+        let away = along.perp();
+        right = climb(right, |j| at(j).dot(along));
+        top = climb(top.max(right), |j| at(j).dot(away));
+        left = climb(left.max(top), |j| -at(j).dot(along));
+
+        let (min_along, max_along) = (at(left).dot(along), at(right).dot(along));
+        let (min_away, max_away) = (at(i).dot(away), at(top).dot(away));
+        let area = (max_along - min_along) * (max_away - min_away);
+        if best.as_ref().is_none_or(|(best_area, _)| area < *best_area) {
+            let center = along * f32::midpoint(min_along, max_along)
+                + away * f32::midpoint(min_away, max_away);
+            let half_extents = Vec2::new(max_along - min_along, max_away - min_away) / 2.0;
+            best = Some((area, OrientedRect::new(center, half_extents, along.angle())));
+        }
+    }
+    best.map(|(_, rect)| rect)
+}
+
+/// The two points furthest apart, found among antipodal pairs of the hull. `None` without points.
+pub fn diameter(points: &[Vec2]) -> Option<(Vec2, Vec2)> {
+    let hull = convex_hull(points);
+    match hull[..] {
+        [] => return None,
+        [point] => return Some((point, point)),
+        [a, b] => return Some((a, b)),
+        _ => {}
+    }
+
+    let count = hull.len();
+    let at = |i: usize| hull[i % count];
+    let mut best = (hull[0], hull[1]);
+    let mut best_distance = 0.0;
+    let mut check = |a: Vec2, b: Vec2| {
+        let distance = a.distance_squared(b);
+        if distance > best_distance {
+            best_distance = distance;
+            best = (a, b);
+        }
+    };
+    let mut opposite = 1;
+    for i in 0..count {
+        let edge = at(i + 1) - at(i);
+        // The vertex furthest from the edge is antipodal to both of its ends.
+        opposite = climb(opposite, |j| edge.cross(at(j) - at(i)));
+        check(at(i), at(opposite));
+        check(at(i + 1), at(opposite));
+    }
+    Some(best)
+}
+
+/// Steps forward while `value` keeps growing, convex hulls have a single peak in any direction.
+fn climb(mut index: usize, value: impl Fn(usize) -> f32) -> usize {
+    while value(index + 1).total_cmp(&value(index)).is_gt() {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+
+    fn scatter(seed: u64, count: usize) -> Vec<Vec2> {
+        let random = Random::new(seed);
+        (0..count)
+            .map(|_| {
+                Vec2::new(
+                    random.random_range(-50.0, 50.0),
+                    random.random_range(-20.0, 30.0),
+                )
+            })
+            .collect()
+    }
+
+    fn ring_edges(ring: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()]))
+    }
+
+    #[test]
+    fn hull_is_convex_and_contains_every_point() {
+        for seed in 0..20 {
+            let points = scatter(seed, 40);
+            let hull = convex_hull(&points);
+            assert!(hull.len() >= 3);
+            assert!(hull.iter().all(|corner| points.contains(corner)));
+            for (a, b) in ring_edges(&hull) {
+                for point in &points {
+                    assert_ne!(orient2d(a, b, *point), Ordering::Less, "{seed}");
+                }
+            }
+            for i in 0..hull.len() {
+                let corner = [
+                    hull[i],
+                    hull[(i + 1) % hull.len()],
+                    hull[(i + 2) % hull.len()],
+                ];
+                assert_eq!(orient2d(corner[0], corner[1], corner[2]), Ordering::Greater);
+            }
+        }
+    }
+
+    #[test]
+    fn hull_leaves_out_edge_and_repeated_points() {
+        let points = [
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        let expected = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert_eq!(convex_hull(&points), expected);
+        let line = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+        ];
+        assert_eq!(convex_hull(&line), [line[0], line[2]]);
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn smallest_circle_matches_brute_force() {
+        for seed in 0..10 {
+            let points = scatter(seed, 20);
+            let circle = min_enclosing_circle(&points).expect("has points");
+            assert!(
+                points.iter().all(|point| encloses(&circle, *point)),
+                "{seed}"
+            );
+
+            let mut smallest = f32::INFINITY;
+            for (i, a) in points.iter().enumerate() {
+                for (j, b) in points.iter().enumerate().skip(i + 1) {
+                    let mut candidates = vec![diameter_circle(*a, *b)];
+                    candidates.extend(points[j + 1..].iter().map(|c| circumcircle(*a, *b, *c)));
+                    for candidate in candidates {
+                        if points.iter().all(|point| encloses(&candidate, *point)) {
+                            smallest = smallest.min(candidate.radius);
+                        }
+                    }
+                }
+            }
+            assert!((circle.radius - smallest).abs() < 1e-3, "{seed}");
+        }
+        let single = min_enclosing_circle(&[Vec2::new(3.0, 4.0)]).expect("has a point");
+        assert_eq!(single, Circle::new(Vec2::new(3.0, 4.0), 0.0));
+        assert_eq!(min_enclosing_circle(&[]), None);
+    }
+
+    #[test]
+    fn smallest_rect_matches_every_hull_edge() {
+        for seed in 0..10 {
+            let points = scatter(seed, 30);
+            let rect = min_area_rect(&points).expect("has points");
+            let grown = OrientedRect::new(
+                rect.center,
+                rect.half_extents + Vec2::new(1e-3, 1e-3),
+                rect.angle,
+            );
+            assert!(points.iter().all(|point| grown.contains(*point)), "{seed}");
+
+            let hull = convex_hull(&points);
+            let smallest = ring_edges(&hull)
+                .map(|(a, b)| {
+                    let along = (b - a).normalize();
+                    let away = along.perp();
+                    let extent = |axis: Vec2| {
+                        let values = hull.iter().map(|point| point.dot(axis));
+                        values.clone().fold(f32::MIN, f32::max) - values.fold(f32::MAX, f32::min)
+                    };
+                    extent(along) * extent(away)
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!((rect.area() - smallest).abs() < smallest * 1e-4, "{seed}");
+        }
+    }
+
+    #[test]
+    fn smallest_rect_finds_a_turned_rectangle() {
+        let turned = OrientedRect::new(Vec2::new(5.0, -3.0), Vec2::new(8.0, 2.0), 0.5);
+        let rect = min_area_rect(&turned.corners()).expect("has points");
+        assert!((rect.area() - turned.area()).abs() < 1e-3);
+        assert!(rect.center.distance(turned.center) < 1e-4);
+
+        let pair = min_area_rect(&[Vec2::new(0.0, 0.0), Vec2::new(0.0, 4.0)]).expect("has points");
+        assert!(pair.area().abs() < 1e-6);
+        assert!(pair.center.distance(Vec2::new(0.0, 2.0)) < 1e-6);
+        assert_eq!(min_area_rect(&[]), None);
+    }
+
+    #[test]
+    fn diameter_matches_brute_force() {
+        for seed in 0..20 {
+            let points = scatter(seed, 50);
+            let (a, b) = diameter(&points).expect("has points");
+            let furthest = points
+                .iter()
+                .flat_map(|a| points.iter().map(|b| a.distance_squared(*b)))
+                .fold(0.0, f32::max);
+            assert_eq!(
+                a.distance_squared(b).to_bits(),
+                furthest.to_bits(),
+                "{seed}"
+            );
+        }
+        assert_eq!(diameter(&[]), None);
+    }
+}
@@ -0,0 +1,639 @@
+//! Where shapes meet, and how close they get when they don't.
+
+use crate::geometry::polygon::{ring_signed_area, FillRule, Polygon};
+use crate::geometry::{Circle, Line, OrientedRect, Rect, Triangle};
+use crate::math::Vec2;
+
+/// Half line from `origin` going along `direction`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    pub direction: Vec2,
+}
+
+impl Ray {
+    pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        Self { origin, direction }
+    }
+
+    /// `t` counts lengths of `direction`.
+    pub fn point_at(&self, t: f32) -> Vec2 {
+        self.origin + self.direction * t
+    }
+}
+
+impl Line {
+    /// Point of the segment nearest to `point`, unlike `project` it stays between the ends.
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let edge = self.b - self.a;
+        let length_squared = edge.length_squared();
+        if length_squared == 0.0 {
+            return self.a;
+        }
+        let t = ((point - self.a).dot(edge) / length_squared).clamp(0.0, 1.0);
+        self.a + edge * t
+    }
+
+    pub fn distance(&self, point: Vec2) -> f32 {
+        self.closest_point(point).distance(point)
+    }
+}
+
+/// Where the first shape runs into the second.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hit {
+    pub point: Vec2,
+    /// How far along the first shape, 0 at a segment's start and 1 at its end,
+    /// in lengths of `direction` along a ray.
+    pub t: f32,
+    /// Unit normal of the second shape there. It points out of closed shapes,
+    /// for segments it faces the side the first shape comes from.
+    pub normal: Vec2,
+}
+
+/// First point of `first` on `second`, collinear overlapping segments meet where the overlap starts.
+pub fn segment_segment(first: &Line, second: &Line) -> Option<Hit> {
+    cast(first.a, first.b - first.a, 1.0, second.a, second.b)
+}
+
+pub fn ray_segment(ray: &Ray, segment: &Line) -> Option<Hit> {
+    cast(
+        ray.origin,
+        ray.direction,
+        f32::INFINITY,
+        segment.a,
+        segment.b,
+    )
+}
+
+/// Both points where the infinite line through the segment's ends crosses the circle,
+/// ordered by `t`. Hits with `t` in [0, 1] are on the segment itself.
+#[allow(clippy::suboptimal_flops)]
+pub fn line_circle(line: &Line, circle: &Circle) -> Vec<Hit> {
+    let direction = line.b - line.a;
+    let offset = line.a - circle.center;
+    let a = direction.length_squared();
+    let b = 2.0 * direction.dot(offset);
+    let c = offset.length_squared() - circle.radius * circle.radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    let mut ts = vec![(-b - root) / (2.0 * a)];
+    if root > 0.0 {
+        ts.push((-b + root) / (2.0 * a));
+    }
+    ts.into_iter()
+        .map(|t| {
+            let point = line.a + direction * t;
+            Hit {
+                point,
+                t,
+                normal: (point - circle.center).normalize(),
+            }
+        })
+        .collect()
+}
+
+/// Every point where the segment crosses the rectangle's border, ordered by `t`.
+pub fn segment_rect(segment: &Line, rect: &Rect) -> Vec<Hit> {
+    let corners: [Vec2; 4] = rect.corners().into();
+    ring_hits(segment.a, segment.b - segment.a, 1.0, &corners)
+}
+
+/// Nearest point where the ray reaches the triangle's border, from inside that is where it leaves.
+pub fn ray_triangle(ray: &Ray, triangle: &Triangle) -> Option<Hit> {
+    let Triangle { a, b, c } = *triangle;
+    ring_hits(ray.origin, ray.direction, f32::INFINITY, &[a, b, c])
+        .into_iter()
+        .next()
+}
+
+/// Nearest point where the ray reaches any ring of the polygon.
+pub fn ray_polygon(ray: &Ray, polygon: &Polygon) -> Option<Hit> {
+    polygon
+        .rings
+        .iter()
+        .flat_map(|ring| ring_hits(ray.origin, ray.direction, f32::INFINITY, ring))
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+/// Points where an edge of one polygon meets an edge of the other, one for each pair of edges.
+pub fn polygon_polygon(first: &Polygon, second: &Polygon) -> Vec<Vec2> {
+    let edges: Vec<(Vec2, Vec2)> = second.edges().collect();
+    first
+        .edges()
+        .flat_map(|(a, b)| {
+            edges
+                .iter()
+                .filter_map(move |(c, d)| cast(a, b - a, 1.0, *c, *d).map(|hit| hit.point))
+        })
+        .collect()
+}
+
+/// Points where the circles' outlines cross, none for circles inside one another or on the same center.
+#[allow(clippy::suboptimal_flops)]
+pub fn circle_circle(first: &Circle, second: &Circle) -> Vec<Vec2> {
+    let between = second.center - first.center;
+    let distance = between.len();
+    if distance == 0.0
+        || distance > first.radius + second.radius
+        || distance < (first.radius - second.radius).abs()
+    {
+        return Vec::new();
+    }
+    // Distance from the first center to the line through both crossings.
+    let along = (first.radius * first.radius - second.radius * second.radius + distance * distance)
+        / (2.0 * distance);
+    let direction = between / distance;
+    let middle = first.center + direction * along;
+    let height = (first.radius * first.radius - along * along)
+        .max(0.0)
+        .sqrt();
+    if height == 0.0 {
+        return vec![middle];
+    }
+    let offset = direction.perp() * height;
+    vec![middle - offset, middle + offset]
+}
+
+/// Where the segment from `start` along `direction`, up to `t_max` of it, meets the segment `c`-`d`.
+fn cast(start: Vec2, direction: Vec2, t_max: f32, c: Vec2, d: Vec2) -> Option<Hit> {
+    let edge = d - c;
+    let to_edge = c - start;
+    let denominator = direction.cross(edge);
+    let facing = |normal: Vec2| {
+        if normal.dot(direction) > 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    };
+    let normal = facing(edge.perp().normalize());
+    if denominator == 0.0 {
+        if to_edge.cross(direction) != 0.0 || to_edge.cross(edge) != 0.0 {
+            return None;
+        }
+        // On one line, the first shared point is where both ranges start overlapping.
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            let on_edge = Line::new(c, d).closest_point(start) == start;
+            return on_edge.then_some(Hit {
+                point: start,
+                t: 0.0,
+                normal,
+            });
+        }
+        let (from, to) = (
+            to_edge.dot(direction) / length_squared,
+            (d - start).dot(direction) / length_squared,
+        );
+        let (low, high) = (from.min(to).max(0.0), from.max(to).min(t_max));
+        return (low <= high).then(|| Hit {
+            point: start + direction * low,
+            t: low,
+            normal,
+        });
+    }
+    let t = to_edge.cross(edge) / denominator;
+    let u = to_edge.cross(direction) / denominator;
+    ((0.0..=t_max).contains(&t) && (0.0..=1.0).contains(&u)).then(|| Hit {
+        point: start + direction * t,
+        t,
+        normal,
+    })
+}
+
+/// Hits on every edge of a closed ring ordered by `t`, a corner only counts once.
+fn ring_hits(start: Vec2, direction: Vec2, t_max: f32, ring: &[Vec2]) -> Vec<Hit> {
+    // Counter-clockwise rings (y up) have their inside on the left of each edge.
+    let outward = if ring_signed_area(ring) < 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+    let mut hits: Vec<Hit> = (0..ring.len())
+        .filter_map(|i| {
+            let (c, d) = (ring[i], ring[(i + 1) % ring.len()]);
+            cast(start, direction, t_max, c, d).map(|hit| Hit {
+                normal: (d - c).perp().normalize() * outward,
+                ..hit
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| a.t.total_cmp(&b.t));
+    hits.dedup_by(|a, b| a.point == b.point);
+    hits
+}
+
+/// Anything `closest_points` and `distance` work between. Circles and polygons are filled.
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Point(Vec2),
+    Segment(Line),
+    Circle(Circle),
+    Polygon(Polygon),
+}
+
+impl From<Vec2> for Shape {
+    fn from(point: Vec2) -> Self {
+        Self::Point(point)
+    }
+}
+
+impl From<Line> for Shape {
+    fn from(line: Line) -> Self {
+        Self::Segment(line)
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(circle: Circle) -> Self {
+        Self::Circle(circle)
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(polygon: Polygon) -> Self {
+        Self::Polygon(polygon)
+    }
+}
+
+impl From<Rect> for Shape {
+    fn from(rect: Rect) -> Self {
+        let corners: [Vec2; 4] = rect.corners().into();
+        Self::Polygon(Polygon::new(corners.to_vec()))
+    }
+}
+
+impl From<Triangle> for Shape {
+    fn from(triangle: Triangle) -> Self {
+        let Triangle { a, b, c } = triangle;
+        Self::Polygon(Polygon::new(vec![a, b, c]))
+    }
+}
+
+impl From<OrientedRect> for Shape {
+    fn from(rect: OrientedRect) -> Self {
+        Self::Polygon(Polygon::new(rect.corners().to_vec()))
+    }
+}
+
+impl Shape {
+    /// Nearest point of the shape, points inside are their own nearest.
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        match self {
+            Self::Point(own) => *own,
+            Self::Segment(line) => line.closest_point(point),
+            Self::Circle(circle) => {
+                let offset = point - circle.center;
+                if offset.length_squared() <= circle.radius * circle.radius {
+                    point
+                } else {
+                    circle.center + offset.normalize() * circle.radius
+                }
+            }
+            Self::Polygon(polygon) => {
+                if polygon.contains(point, FillRule::NonZero) {
+                    return point;
+                }
+                polygon
+                    .edges()
+                    .map(|(a, b)| Line::new(a, b).closest_point(point))
+                    .min_by(|a, b| {
+                        a.distance_squared(point)
+                            .total_cmp(&b.distance_squared(point))
+                    })
+                    .unwrap_or(point)
+            }
+        }
+    }
+
+    pub fn distance(&self, point: Vec2) -> f32 {
+        self.closest_point(point).distance(point)
+    }
+
+    fn vertices(&self) -> Vec<Vec2> {
+        match self {
+            Self::Point(point) => vec![*point],
+            Self::Segment(line) => vec![line.a, line.b],
+            Self::Circle(circle) => vec![circle.center],
+            Self::Polygon(polygon) => polygon.rings.concat(),
+        }
+    }
+
+    fn edges(&self) -> Vec<(Vec2, Vec2)> {
+        match self {
+            Self::Point(_) | Self::Circle(_) => Vec::new(),
+            Self::Segment(line) => vec![(line.a, line.b)],
+            Self::Polygon(polygon) => polygon.edges().collect(),
+        }
+    }
+}
+
+/// A point of each shape, as close to each other as the shapes get. Overlapping shapes
+/// get the same point twice.
+pub fn closest_points(first: &Shape, second: &Shape) -> (Vec2, Vec2) {
+    match (first, second) {
+        (Shape::Point(point), other) => (*point, other.closest_point(*point)),
+        (other, Shape::Point(point)) => (other.closest_point(*point), *point),
+        (Shape::Circle(circle), other) => circle_closest(circle, other),
+        (other, Shape::Circle(circle)) => {
+            let (on_circle, on_other) = circle_closest(circle, other);
+            (on_other, on_circle)
+        }
+        _ => {
+            for (a, b) in first.edges() {
+                for (c, d) in second.edges() {
+                    if let Some(hit) = cast(a, b - a, 1.0, c, d) {
+                        return (hit.point, hit.point);
+                    }
+                }
+            }
+            // Without crossing edges, the nearest pair has a vertex of one shape in it.
+            let from_first = first
+                .vertices()
+                .into_iter()
+                .map(|vertex| (vertex, second.closest_point(vertex)));
+            let from_second = second
+                .vertices()
+                .into_iter()
+                .map(|vertex| (first.closest_point(vertex), vertex));
+            from_first
+                .chain(from_second)
+                .min_by(|(a, b), (c, d)| a.distance_squared(*b).total_cmp(&c.distance_squared(*d)))
+                .expect("segments and polygons have vertices")
+        }
+    }
+}
+
+pub fn distance(first: &Shape, second: &Shape) -> f32 {
+    let (a, b) = closest_points(first, second);
+    a.distance(b)
+}
+
+/// The other shape is nearest the circle where it is nearest the center.
+fn circle_closest(circle: &Circle, other: &Shape) -> (Vec2, Vec2) {
+    let nearest = other.closest_point(circle.center);
+    let offset = nearest - circle.center;
+    if offset.length_squared() <= circle.radius * circle.radius {
+        return (nearest, nearest);
+    }
+    (circle.center + offset.normalize() * circle.radius, nearest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::random::Random;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-4
+    }
+
+    fn line(ax: f32, ay: f32, bx: f32, by: f32) -> Line {
+        Line::new(Vec2::new(ax, ay), Vec2::new(bx, by))
+    }
+
+    fn scatter(seed: u64, count: usize, size: f32) -> Vec<Vec2> {
+        let random = Random::new(seed);
+        (0..count)
+            .map(|_| {
+                Vec2::new(
+                    random.random_range(-size, size),
+                    random.random_range(-size, size),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crossing_segments() {
+        let hit = segment_segment(&line(0.0, 0.0, 4.0, 4.0), &line(0.0, 4.0, 4.0, 0.0)).unwrap();
+        assert!(close(hit.point, Vec2::new(2.0, 2.0)));
+        assert!((hit.t - 0.5).abs() < 1e-6);
+        // The normal faces back towards the start of the first segment.
+        assert!(hit.normal.dot(Vec2::new(1.0, 1.0)) < 0.0);
+        assert!((hit.normal.len() - 1.0).abs() < 1e-6);
+
+        // Touching at an end counts, passing short of the other segment doesn't.
+        let touch = segment_segment(&line(0.0, 0.0, 2.0, 0.0), &line(2.0, -1.0, 2.0, 1.0)).unwrap();
+        assert!(close(touch.point, Vec2::new(2.0, 0.0)) && (touch.t - 1.0).abs() < 1e-6);
+        assert!(segment_segment(&line(0.0, 0.0, 1.9, 0.0), &line(2.0, -1.0, 2.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn parallel_and_collinear_segments() {
+        assert!(segment_segment(&line(0.0, 0.0, 4.0, 0.0), &line(0.0, 1.0, 4.0, 1.0)).is_none());
+        // On one line but apart.
+        assert!(segment_segment(&line(0.0, 0.0, 1.0, 0.0), &line(2.0, 0.0, 3.0, 0.0)).is_none());
+
+        // Overlapping, the hit is where the overlap starts along the first segment.
+        let hit = segment_segment(&line(0.0, 0.0, 4.0, 0.0), &line(3.0, 0.0, 1.0, 0.0)).unwrap();
+        assert!(close(hit.point, Vec2::new(1.0, 0.0)) && (hit.t - 0.25).abs() < 1e-6);
+        let inside =
+            segment_segment(&line(0.0, 0.0, 1.1, 2.7), &line(-1.1, -2.7, 1.1, 2.7)).unwrap();
+        assert_eq!(inside.t.to_bits(), 0.0_f32.to_bits());
+        assert_eq!(inside.point, Vec2::new(0.0, 0.0));
+
+        // Meeting end to end, and a segment that is a single point.
+        let end = segment_segment(&line(0.0, 0.0, 2.0, 0.0), &line(2.0, 0.0, 5.0, 0.0)).unwrap();
+        assert!(close(end.point, Vec2::new(2.0, 0.0)));
+        let point = line(1.0, 0.0, 1.0, 0.0);
+        assert!(segment_segment(&point, &line(0.0, 0.0, 2.0, 0.0)).is_some());
+        assert!(segment_segment(&point, &line(0.0, 1.0, 2.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn rays_reach_past_one() {
+        let ray = Ray::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+        let hit = ray_segment(&ray, &line(10.0, -1.0, 10.0, 1.0)).unwrap();
+        assert!((hit.t - 10.0).abs() < 1e-5 && close(ray.point_at(hit.t), hit.point));
+        assert_eq!(hit.normal, Vec2::new(-1.0, 0.0));
+        // Nothing behind the origin.
+        assert!(ray_segment(&ray, &line(-10.0, -1.0, -10.0, 1.0)).is_none());
+        let along = ray_segment(&ray, &line(-3.0, 0.0, 5.0, 0.0)).unwrap();
+        assert_eq!(along.t.to_bits(), 0.0_f32.to_bits());
+    }
+
+    #[test]
+    fn lines_and_circles() {
+        let circle = Circle::new(Vec2::new(5.0, 0.0), 2.0);
+        let hits = line_circle(&line(0.0, 0.0, 10.0, 0.0), &circle);
+        assert_eq!(hits.len(), 2);
+        assert!(
+            close(hits[0].point, Vec2::new(3.0, 0.0)) && close(hits[1].point, Vec2::new(7.0, 0.0))
+        );
+        assert!((hits[0].t - 0.3).abs() < 1e-6 && (hits[1].t - 0.7).abs() < 1e-6);
+        assert!(close(hits[0].normal, Vec2::new(-1.0, 0.0)));
+        // The line runs on past a short segment.
+        let beyond = line_circle(&line(0.0, 0.0, 1.0, 0.0), &circle);
+        assert!(beyond[0].t > 1.0);
+        assert_eq!(line_circle(&line(0.0, 2.0, 1.0, 2.0), &circle).len(), 1);
+        assert!(line_circle(&line(0.0, 3.0, 1.0, 3.0), &circle).is_empty());
+        assert!(line_circle(&line(5.0, 0.0, 5.0, 0.0), &circle).is_empty());
+    }
+
+    #[test]
+    fn circles_meet() {
+        let first = Circle::new(Vec2::new(0.0, 0.0), 5.0);
+        let points = circle_circle(&first, &Circle::new(Vec2::new(8.0, 0.0), 5.0));
+        assert_eq!(points.len(), 2);
+        assert!(close(points[0], Vec2::new(4.0, -3.0)) || close(points[0], Vec2::new(4.0, 3.0)));
+        for point in &points {
+            assert!((point.len() - 5.0).abs() < 1e-4);
+            assert!((point.distance(Vec2::new(8.0, 0.0)) - 5.0).abs() < 1e-4);
+        }
+        let touching = circle_circle(&first, &Circle::new(Vec2::new(7.0, 0.0), 2.0));
+        assert_eq!(touching.len(), 1);
+        assert!(close(touching[0], Vec2::new(5.0, 0.0)));
+        assert!(circle_circle(&first, &Circle::new(Vec2::new(20.0, 0.0), 5.0)).is_empty());
+        assert!(circle_circle(&first, &Circle::new(Vec2::new(1.0, 0.0), 1.0)).is_empty());
+        assert!(circle_circle(&first, &first).is_empty());
+    }
+
+    #[test]
+    fn rects_triangles_and_polygons() {
+        let rect = Rect::new(Vec2::new(0.0, 0.0), Size::new(10, 10));
+        let hits = segment_rect(&line(-5.0, 5.0, 15.0, 5.0), &rect);
+        assert_eq!(hits.len(), 2);
+        assert!(
+            close(hits[0].point, Vec2::new(0.0, 5.0)) && close(hits[1].point, Vec2::new(10.0, 5.0))
+        );
+        assert!(hits[0].t < hits[1].t);
+        // Normals point out of the rectangle on both sides.
+        assert!(hits[0].normal.x < 0.0 && hits[1].normal.x > 0.0);
+        // Through a corner only once.
+        assert_eq!(segment_rect(&line(-5.0, -5.0, 5.0, 5.0), &rect).len(), 1);
+
+        let triangle = Triangle::new(
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(0.0, 4.0),
+        );
+        let from_inside = ray_triangle(
+            &Ray::new(Vec2::new(1.0, 1.0), Vec2::new(1.0, 0.0)),
+            &triangle,
+        )
+        .unwrap();
+        assert!(close(from_inside.point, Vec2::new(3.0, 1.0)));
+        assert!(from_inside.normal.dot(Vec2::new(1.0, 1.0)) > 0.0);
+        assert!(ray_triangle(
+            &Ray::new(Vec2::new(5.0, 5.0), Vec2::new(1.0, 0.0)),
+            &triangle
+        )
+        .is_none());
+
+        let ring = |x: f32, size: f32| {
+            vec![
+                Vec2::new(x, -size),
+                Vec2::new(x + size, -size),
+                Vec2::new(x + size, size),
+                Vec2::new(x, size),
+            ]
+        };
+        let polygon = Polygon::new(ring(2.0, 3.0)).with_hole(ring(3.0, 1.0));
+        let ray = Ray::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+        assert!((ray_polygon(&ray, &polygon).unwrap().t - 2.0).abs() < 1e-6);
+        let inside_hole = Ray::new(Vec2::new(3.5, 0.0), Vec2::new(1.0, 0.0));
+        assert!((ray_polygon(&inside_hole, &polygon).unwrap().t - 0.5).abs() < 1e-6);
+
+        let across = ring(1.5, 1.0);
+        let crossings = polygon_polygon(&Polygon::new(ring(0.0, 2.0)), &Polygon::new(across));
+        assert_eq!(crossings.len(), 2);
+        assert!(crossings
+            .iter()
+            .all(|point| (point.y.abs() - 1.0).abs() < 1e-6 && (point.x - 2.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn closest_points_match_a_scan() {
+        let shapes: Vec<Shape> = vec![
+            Vec2::new(20.0, 0.0).into(),
+            line(-20.0, 15.0, -5.0, 25.0).into(),
+            Circle::new(Vec2::new(0.0, -20.0), 4.0).into(),
+            Triangle::new(
+                Vec2::new(10.0, 10.0),
+                Vec2::new(18.0, 12.0),
+                Vec2::new(12.0, 20.0),
+            )
+            .into(),
+            OrientedRect::new(Vec2::new(-15.0, -10.0), Vec2::new(4.0, 2.0), 0.6).into(),
+            Rect::new(Vec2::new(2.0, 2.0), Size::new(3, 4)).into(),
+        ];
+        // Fine samples of each shape's outline, filled shapes are nearest at their border when apart.
+        let samples = |shape: &Shape| -> Vec<Vec2> {
+            let along = |a: Vec2, b: Vec2| (0..=400).map(move |i| a.lerp(b, i as f32 / 400.0));
+            match shape {
+                Shape::Point(point) => vec![*point],
+                Shape::Circle(circle) => (0..1000)
+                    .map(|i| {
+                        circle.center
+                            + Vec2::from_angle(i as f32 * std::f32::consts::TAU / 1000.0)
+                                * circle.radius
+                    })
+                    .collect(),
+                _ => shape
+                    .edges()
+                    .into_iter()
+                    .flat_map(|(a, b)| along(a, b))
+                    .collect(),
+            }
+        };
+        for first in &shapes {
+            for second in &shapes {
+                if std::ptr::eq(first, second) {
+                    continue;
+                }
+                let (a, b) = closest_points(first, second);
+                assert!(close(first.closest_point(a), a) && close(second.closest_point(b), b));
+                let scan = samples(first)
+                    .iter()
+                    .flat_map(|a| samples(second).into_iter().map(move |b| a.distance(b)))
+                    .fold(f32::INFINITY, f32::min);
+                let found = distance(first, second);
+                assert!((found - a.distance(b)).abs() < 1e-5);
+                assert!(
+                    found <= scan + 1e-4 && scan - found < 0.05,
+                    "{first:?} {second:?}: {found} {scan}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_shapes_share_a_point() {
+        let square = Shape::from(Rect::new(Vec2::new(0.0, 0.0), Size::new(4, 4)));
+        let circle = Shape::from(Circle::new(Vec2::new(5.0, 2.0), 2.0));
+        let (a, b) = closest_points(&square, &circle);
+        assert_eq!(a, b);
+        assert!(
+            distance(&square, &Shape::from(Vec2::new(1.0, 1.0))).to_bits() == 0.0_f32.to_bits()
+        );
+        let crossing = Shape::from(line(-1.0, 2.0, 5.0, 2.0));
+        assert!(distance(&square, &crossing).to_bits() == 0.0_f32.to_bits());
+        // A segment wholly inside the filled shape.
+        assert!(
+            distance(&circle, &Shape::from(line(4.5, 2.0, 5.5, 2.0))).to_bits()
+                == 0.0_f32.to_bits()
+        );
+    }
+
+    #[test]
+    fn segment_distance_matches_brute_force() {
+        for (index, point) in scatter(7, 200, 10.0).into_iter().enumerate() {
+            let segment = line(-3.0, 1.0, 4.0, -2.0);
+            let scan = (0..=2000)
+                .map(|i| segment.a.lerp(segment.b, i as f32 / 2000.0).distance(point))
+                .fold(f32::INFINITY, f32::min);
+            let found = segment.distance(point);
+            assert!(found <= scan + 1e-5 && scan - found < 0.01, "{index}");
+        }
+        assert_eq!(
+            line(1.0, 1.0, 1.0, 1.0).closest_point(Vec2::new(5.0, 5.0)),
+            Vec2::new(1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use std::cmp::Ordering;
+
+use crate::geometry::Rect;
+use crate::graphics::line;
+use crate::math::{Affine2, Vec2};
+use crate::predicates::orient2d;
+
+/// Decides which points a polygon with overlapping or nested rings covers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside when the rings wind around the point any number of times.
+    #[default]
+    NonZero,
+    /// Inside when a ray from the point crosses an odd number of edges, holes need no particular orientation.
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Any number of closed rings, the last point of each connects back to its first.
+/// Outer rings and holes are told apart by nesting, `orient` makes their directions agree with it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    pub rings: Vec<Vec<Vec2>>,
+}
+
+impl Polygon {
+    pub fn new(outer: Vec<Vec2>) -> Self {
+        Self { rings: vec![outer] }
+    }
+
+    pub fn from_rings(rings: Vec<Vec<Vec2>>) -> Self {
+        Self { rings }
+    }
+
+    pub fn with_hole(mut self, hole: Vec<Vec2>) -> Self {
+        self.rings.push(hole);
+        self
+    }
+
+    /// Every edge of every ring, including the closing ones.
+    pub fn edges(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.rings.iter().flat_map(|ring| ring_edges(ring))
+    }
+
+    /// Sum of the rings' signed areas, positive for counter-clockwise rings with y up
+    /// (clockwise on screen), see `predicates::orient2d`.
+    pub fn signed_area(&self) -> f32 {
+        self.rings.iter().map(|ring| ring_signed_area(ring)).sum()
+    }
+
+    /// Covered area, holes are subtracted whichever way they are wound.
+    pub fn area(&self) -> f32 {
+        self.rings
+            .iter()
+            .zip(self.ring_depths())
+            .map(|(ring, depth)| depth_sign(depth) * ring_signed_area(ring).abs())
+            .sum()
+    }
+
+    /// Center of mass of the covered area, `None` when there is no area.
+    pub fn centroid(&self) -> Option<Vec2> {
+        let mut total_area = 0.0;
+        let mut weighted = Vec2::default();
+        for (ring, depth) in self.rings.iter().zip(self.ring_depths()) {
+            let area = ring_signed_area(ring);
+            if area == 0.0 {
+                continue;
+            }
+            let moment: Vec2 = ring_edges(ring)
+                .map(|(a, b)| (a + b) * a.cross(b))
+                .sum::<Vec2>()
+                / (6.0 * area);
+            let area = depth_sign(depth) * area.abs();
+            weighted += moment * area;
+            total_area += area;
+        }
+        (total_area.abs() > f32::EPSILON).then(|| weighted / total_area)
+    }
+
+    pub fn perimeter(&self) -> f32 {
+        self.edges().map(|(a, b)| a.distance(b)).sum()
+    }
+
+    /// How many times the rings wind around `point`, counter-clockwise (y up) counts up.
+    /// Points exactly on an edge may land on either side.
+    pub fn winding_number(&self, point: Vec2) -> i32 {
+        self.edges().map(|(a, b)| edge_winding(a, b, point)).sum()
+    }
+
+    pub fn contains(&self, point: Vec2, rule: FillRule) -> bool {
+        rule.is_inside(self.winding_number(point))
+    }
+
+    /// Winds outer rings counter-clockwise (y up) and holes the other way, so both fill rules
+    /// agree and `signed_area` equals `area`. Rings without area are dropped.
+    pub fn orient(&mut self) {
+        let depths = self.ring_depths();
+        let mut depths = depths.into_iter();
+        self.rings.retain_mut(|ring| {
+            let depth = depths.next().unwrap_or_default();
+            let area = ring_signed_area(ring);
+            if area == 0.0 {
+                return false;
+            }
+            if (area > 0.0) != depth.is_multiple_of(2) {
+                ring.reverse();
+            }
+            true
+        });
+    }
+
+    pub fn oriented(mut self) -> Self {
+        self.orient();
+        self
+    }
+
+    /// Splits into polygons of one outer ring and the holes directly inside it,
+    /// oriented like `orient` does. Islands inside holes become parts of their own.
+    pub fn parts(&self) -> Vec<Self> {
+        let oriented = self.clone().oriented();
+        let depths = oriented.ring_depths();
+        let mut parts: Vec<(usize, Self)> = Vec::new();
+        for (index, depth) in depths.iter().enumerate() {
+            if depth.is_multiple_of(2) {
+                parts.push((index, Self::new(oriented.rings[index].clone())));
+            }
+        }
+        for (index, depth) in depths.iter().enumerate() {
+            if depth.is_multiple_of(2) {
+                continue;
+            }
+            let hole = &oriented.rings[index];
+            let parent = parts.iter_mut().find(|(outer, _)| {
+                depths[*outer] + 1 == *depth && ring_inside(hole, &oriented.rings[*outer])
+            });
+            if let Some((_, part)) = parent {
+                part.rings.push(hole.clone());
+            }
+        }
+        parts.into_iter().map(|(_, part)| part).collect()
+    }
+
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::from_rings(
+            self.rings
+                .iter()
+                .map(|ring| {
+                    ring.iter()
+                        .map(|point| transform.transform_point(*point))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn bounding(&self) -> Rect {
+        let points: Vec<Vec2> = self.rings.iter().flatten().copied().collect();
+        Rect::bounding(&points)
+    }
+
+    /// Pixels with integer coordinates inside the polygon, found a row at a time from the edge crossings.
+    #[allow(clippy::suboptimal_flops)]
+    pub fn solid_color(&self, rule: FillRule) -> Vec<Vec2> {
+        let mut points = Vec::new();
+        let Some((top, bottom)) =
+            self.rings
+                .iter()
+                .flatten()
+                .fold(None, |range: Option<(f32, f32)>, point| {
+                    Some(range.map_or((point.y, point.y), |(top, bottom)| {
+                        (top.min(point.y), bottom.max(point.y))
+                    }))
+                })
+        else {
+            return points;
+        };
+
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for y in top.ceil() as i32..=bottom.floor() as i32 {
+            let row = y as f32;
+            crossings.clear();
+            for (a, b) in self.edges() {
+                // Half open in y, so a vertex on the row is counted once.
+                if (a.y <= row) != (b.y <= row) {
+                    let x = a.x + (row - a.y) / (b.y - a.y) * (b.x - a.x);
+                    crossings.push((x, if b.y > a.y { 1 } else { -1 }));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if rule.is_inside(winding) {
+                    for x in pair[0].0.ceil() as i32..pair[1].0.ceil() as i32 {
+                        points.push(Vec2::new(x as f32, row));
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    pub fn empty(&self) -> Vec<Vec2> {
+        self.edges().flat_map(|(a, b)| line(a, b)).collect()
+    }
+
+    /// How many other rings contain each ring, even depths are outer rings and odd ones holes.
+    fn ring_depths(&self) -> Vec<usize> {
+        self.rings
+            .iter()
+            .enumerate()
+            .map(|(index, ring)| {
+                self.rings
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, other)| {
+                        *other_index != index && ring_inside(ring, other)
+                    })
+                    .count()
+            })
+            .collect()
+    }
+}
+
+fn depth_sign(depth: usize) -> f32 {
+    if depth.is_multiple_of(2) {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn ring_edges(ring: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    let count = ring.len();
+    (0..count).map(move |i| (ring[i], ring[(i + 1) % count]))
+}
+
+/// Whether `ring` lies inside `other`, judged by a point of `ring` that isn't on `other`,
+/// so rings touching at a vertex or along an edge aren't taken for nested.
+fn ring_inside(ring: &[Vec2], other: &[Vec2]) -> bool {
+    let on_other = |point: Vec2| {
+        ring_edges(other).any(|(a, b)| {
+            orient2d(a, b, point) == Ordering::Equal
+                && point.x >= a.x.min(b.x)
+                && point.x <= a.x.max(b.x)
+                && point.y >= a.y.min(b.y)
+                && point.y <= a.y.max(b.y)
+        })
+    };
+    ring.iter()
+        .copied()
+        .chain(ring_edges(ring).map(|(a, b)| (a + b) / 2.0))
+        .find(|point| !on_other(*point))
+        .is_some_and(|point| ring_winding_number(other, point) != 0)
+}
+
+fn ring_winding_number(ring: &[Vec2], point: Vec2) -> i32 {
+    ring_edges(ring)
+        .map(|(a, b)| edge_winding(a, b, point))
+        .sum()
+}
+
+/// +1 when the edge passes upwards (y up) to the right of `point`, -1 when it passes downwards.
+fn edge_winding(a: Vec2, b: Vec2, point: Vec2) -> i32 {
+    if a.y <= point.y {
+        i32::from(b.y > point.y && orient2d(a, b, point) == Ordering::Greater)
+    } else {
+        -i32::from(b.y <= point.y && orient2d(a, b, point) == Ordering::Less)
+    }
+}
+
+/// Shoelace formula, positive for counter-clockwise rings with y up.
+pub fn ring_signed_area(ring: &[Vec2]) -> f32 {
+    ring_edges(ring).map(|(a, b)| a.cross(b)).sum::<f32>() / 2.0
+}
+
+/// The ring without repeated points and points on a straight line between their neighbours,
+/// empty when nothing with an area is left.
+pub fn clean_ring(ring: &[Vec2]) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = ring.to_vec();
+    points.dedup();
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let mut index = 0;
+    let mut unchanged = 0;
+    while points.len() >= 3 && unchanged < points.len() {
+        let count = points.len();
+        let (previous, current, next) = (
+            points[(index + count - 1) % count],
+            points[index % count],
+            points[(index + 1) % count],
+        );
+        if orient2d(previous, current, next) == Ordering::Equal {
+            points.remove(index % count);
+            unchanged = 0;
+        } else {
+            index = (index + 1) % count;
+            unchanged += 1;
+        }
+    }
+    if points.len() < 3 {
+        points.clear();
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counter-clockwise with y up.
+    fn square(x: f32, y: f32, size: f32) -> Vec<Vec2> {
+        vec![
+            Vec2::new(x, y),
+            Vec2::new(x + size, y),
+            Vec2::new(x + size, y + size),
+            Vec2::new(x, y + size),
+        ]
+    }
+
+    fn reversed(mut ring: Vec<Vec2>) -> Vec<Vec2> {
+        ring.reverse();
+        ring
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn holes_are_subtracted_whichever_way_they_wind() {
+        for hole in [square(2.0, 2.0, 2.0), reversed(square(2.0, 2.0, 2.0))] {
+            let polygon = Polygon::new(square(0.0, 0.0, 10.0)).with_hole(hole);
+            assert!(close(polygon.area(), 96.0));
+            assert!(close(polygon.perimeter(), 48.0));
+            assert!(!polygon.contains(Vec2::new(3.0, 3.0), FillRule::EvenOdd));
+            assert!(polygon.contains(Vec2::new(7.0, 7.0), FillRule::EvenOdd));
+            let oriented = polygon.oriented();
+            assert!(close(oriented.signed_area(), 96.0));
+            assert!(!oriented.contains(Vec2::new(3.0, 3.0), FillRule::NonZero));
+        }
+    }
+
+    #[test]
+    fn fill_rules_differ_where_rings_overlap() {
+        let polygon = Polygon::from_rings(vec![square(0.0, 0.0, 4.0), square(2.0, 2.0, 4.0)]);
+        let overlap = Vec2::new(3.0, 3.0);
+        assert_eq!(polygon.winding_number(overlap), 2);
+        assert!(polygon.contains(overlap, FillRule::NonZero));
+        assert!(!polygon.contains(overlap, FillRule::EvenOdd));
+        assert!(polygon.contains(Vec2::new(1.0, 1.0), FillRule::EvenOdd));
+        assert_eq!(polygon.winding_number(Vec2::new(7.0, 7.0)), 0);
+        // Screen order (clockwise with y up) winds the other way.
+        let clockwise = Polygon::new(reversed(square(0.0, 0.0, 4.0)));
+        assert_eq!(clockwise.winding_number(Vec2::new(1.0, 1.0)), -1);
+    }
+
+    #[test]
+    fn centroid_moves_away_from_the_hole() {
+        let polygon = Polygon::new(square(0.0, 0.0, 4.0)).with_hole(square(0.0, 0.0, 2.0));
+        // Three unit-weighted 2x2 squares centered at (3, 1), (1, 3) and (3, 3).
+        let centroid = polygon.centroid().expect("has area");
+        assert!(close(centroid.x, 7.0 / 3.0) && close(centroid.y, 7.0 / 3.0));
+        let flat = Polygon::new(vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)]);
+        assert_eq!(flat.centroid(), None);
+    }
+
+    #[test]
+    fn touching_rings_are_not_nested() {
+        // The second square shares the whole left edge of the first.
+        let polygon = Polygon::from_rings(vec![square(0.0, 0.0, 2.0), square(2.0, 0.0, 2.0)]);
+        assert!(close(polygon.area(), 8.0));
+        assert_eq!(polygon.parts().len(), 2);
+        // A hole touching the outer ring at a corner is still a hole.
+        let notched = Polygon::new(square(0.0, 0.0, 4.0)).with_hole(square(0.0, 0.0, 1.0));
+        assert!(close(notched.area(), 15.0));
+    }
+
+    #[test]
+    fn parts_split_islands_from_holes() {
+        let polygon = Polygon::from_rings(vec![
+            square(3.0, 3.0, 2.0),
+            reversed(square(0.0, 0.0, 8.0)),
+            square(2.0, 2.0, 4.0),
+        ]);
+        let parts = polygon.parts();
+        assert_eq!(parts.len(), 2);
+        let areas: Vec<f32> = parts.iter().map(Polygon::area).collect();
+        assert!(areas.iter().any(|area| close(*area, 48.0)), "{areas:?}");
+        assert!(areas.iter().any(|area| close(*area, 4.0)), "{areas:?}");
+        for part in &parts {
+            assert!(close(part.signed_area(), part.area()));
+        }
+    }
+
+    #[test]
+    fn scanline_fill_matches_the_area() {
+        // Both rings wind the same way, only `EvenOdd` leaves the hole out before orienting.
+        let polygon = Polygon::new(square(0.0, 0.0, 6.0)).with_hole(square(2.0, 2.0, 2.0));
+        assert_eq!(polygon.solid_color(FillRule::NonZero).len(), 36);
+        let oriented = polygon.clone().oriented();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let points = oriented.solid_color(rule);
+            assert_eq!(points.len(), 32);
+            for point in &points {
+                let center = *point + Vec2::new(0.5, 0.5);
+                assert!(polygon.contains(center, FillRule::EvenOdd), "{point:?}");
+            }
+        }
+        assert!(Polygon::default().solid_color(FillRule::NonZero).is_empty());
+    }
+
+    #[test]
+    fn clean_ring_drops_repeats_and_straight_points() {
+        let ring = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        let cleaned = clean_ring(&ring);
+        assert_eq!(cleaned.len(), 4);
+        assert!(close(ring_signed_area(&cleaned), 4.0));
+        let line = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 3.0),
+        ];
+        assert!(clean_ring(&line).is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+//! Smooth curves through or along a list of points, both kinds are converted to cubic Bezier segments.
+
+use crate::geometry::bezier::CubicBezier;
+use crate::math::Vec2;
+
+/// How the knots of a Catmull-Rom spline are spaced, it changes how the curve behaves around sharp turns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Parameterization {
+    /// Evenly spaced knots, can loop and cusp around close points.
+    Uniform,
+    /// Knots spaced by the square root of the distance, never loops or cusps.
+    Centripetal,
+    /// Knots spaced by the distance, the widest curves.
+    Chordal,
+}
+
+impl Parameterization {
+    fn alpha(self) -> f32 {
+        match self {
+            Self::Uniform => 0.0,
+            Self::Centripetal => 0.5,
+            Self::Chordal => 1.0,
+        }
+    }
+}
+
+/// Interpolating spline, it passes through every point.
+#[derive(Clone, Debug)]
+pub struct CatmullRom {
+    pub points: Vec<Vec2>,
+    pub parameterization: Parameterization,
+    /// 0 is the usual curve, 1 straightens it into the polygon through the points.
+    pub tension: f32,
+    /// Connects the last point back to the first.
+    pub closed: bool,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self {
+            points,
+            parameterization: Parameterization::Centripetal,
+            tension: 0.0,
+            closed: false,
+        }
+    }
+
+    pub fn parameterization(self, parameterization: Parameterization) -> Self {
+        Self {
+            parameterization,
+            ..self
+        }
+    }
+
+    pub fn tension(self, tension: f32) -> Self {
+        Self { tension, ..self }
+    }
+
+    pub fn closed(self, closed: bool) -> Self {
+        Self { closed, ..self }
+    }
+
+    /// One segment between each pair of neighbouring points.
+    pub fn segments(&self) -> Vec<CubicBezier> {
+        let alpha = self.parameterization.alpha();
+        let scale = 1.0 - self.tension;
+        neighbourhoods(&self.points, self.closed, |before, from, to, after| {
+            // Knot intervals, duplicate points get a unit interval instead of dividing by zero.
+            let knot = |a: Vec2, b: Vec2| {
+                let interval = a.distance(b).powf(alpha);
+                if interval > f32::EPSILON {
+                    interval
+                } else {
+                    1.0
+                }
+            };
+            let (d0, d1, d2) = (knot(before, from), knot(from, to), knot(to, after));
+
+            let start_tangent =
+                ((from - before) / d0 - (to - before) / (d0 + d1) + (to - from) / d1) * d1;
+            let end_tangent =
+                ((to - from) / d1 - (after - from) / (d1 + d2) + (after - to) / d2) * d1;
+            CubicBezier::new(
+                from,
+                from + start_tangent * (scale / 3.0),
+                to - end_tangent * (scale / 3.0),
+                to,
+            )
+        })
+    }
+
+    /// Polyline within `tolerance` pixels of the curve.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        flatten(&self.segments(), tolerance)
+    }
+
+    pub fn solid(&self) -> Vec<Vec2> {
+        solid(&self.segments())
+    }
+}
+
+/// Uniform cubic B-spline, smoother than `CatmullRom` but only passes near the points.
+/// Open splines are clamped so they still start and end on the first and last point.
+#[derive(Clone, Debug)]
+pub struct BSpline {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+impl BSpline {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self {
+            points,
+            closed: false,
+        }
+    }
+
+    pub fn closed(self, closed: bool) -> Self {
+        Self { closed, ..self }
+    }
+
+    pub fn segments(&self) -> Vec<CubicBezier> {
+        let points = if self.closed || self.points.len() < 2 {
+            self.points.clone()
+        } else {
+            // Repeating the end points pulls the curve onto them.
+            let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+            let mut clamped = vec![first, first];
+            clamped.extend(&self.points);
+            clamped.extend([last, last]);
+            clamped
+        };
+        let count = points.len();
+        if count < 4 && !(self.closed && count >= 3) {
+            return Vec::new();
+        }
+        let segment_count = if self.closed { count } else { count - 3 };
+        (0..segment_count)
+            .map(|i| {
+                let [p0, p1, p2, p3] = std::array::from_fn(|j| points[(i + j) % count]);
+                CubicBezier::new(
+                    (p0 + p1 * 4.0 + p2) / 6.0,
+                    (p1 * 2.0 + p2) / 3.0,
+                    (p1 + p2 * 2.0) / 3.0,
+                    (p1 + p2 * 4.0 + p3) / 6.0,
+                )
+            })
+            .collect()
+    }
+
+    pub fn flatten(&self, tolerance: f32) -> Vec<Vec2> {
+        flatten(&self.segments(), tolerance)
+    }
+
+    pub fn solid(&self) -> Vec<Vec2> {
+        solid(&self.segments())
+    }
+}
+
+/// Calls `segment` with every pair of neighbouring points and the points on either side of them.
+/// Open curves get mirrored phantom points past their ends.
+fn neighbourhoods(
+    points: &[Vec2],
+    closed: bool,
+    segment: impl Fn(Vec2, Vec2, Vec2, Vec2) -> CubicBezier,
+) -> Vec<CubicBezier> {
+    let count = points.len();
+    if count < 2 || (closed && count < 3) {
+        return Vec::new();
+    }
+    let at = |i: isize| -> Vec2 {
+        if closed {
+            points[i.rem_euclid(count as isize) as usize]
+        } else if i < 0 {
+            points[0] * 2.0 - points[1]
+        } else if i as usize >= count {
+            points[count - 1] * 2.0 - points[count - 2]
+        } else {
+            points[i as usize]
+        }
+    };
+    let segment_count = if closed { count } else { count - 1 };
+    (0..segment_count as isize)
+        .map(|i| segment(at(i - 1), at(i), at(i + 1), at(i + 2)))
+        .collect()
+}
+
+fn flatten(segments: &[CubicBezier], tolerance: f32) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = Vec::new();
+    for segment in segments {
+        let polyline = segment.flatten(tolerance);
+        // Every segment starts where the previous one ended.
+        let skip = usize::from(!points.is_empty());
+        points.extend(polyline.into_iter().skip(skip));
+    }
+    points
+}
+
+fn solid(segments: &[CubicBezier]) -> Vec<Vec2> {
+    segments.iter().flat_map(CubicBezier::solid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zigzag() -> Vec<Vec2> {
+        vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 30.0),
+            Vec2::new(12.0, 31.0),
+            Vec2::new(50.0, -10.0),
+            Vec2::new(80.0, 20.0),
+        ]
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-3
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_every_point() {
+        let points = zigzag();
+        for parameterization in [
+            Parameterization::Uniform,
+            Parameterization::Centripetal,
+            Parameterization::Chordal,
+        ] {
+            let spline = CatmullRom::new(points.clone()).parameterization(parameterization);
+            let segments = spline.segments();
+            assert_eq!(segments.len(), points.len() - 1);
+            for (segment, pair) in segments.iter().zip(points.windows(2)) {
+                assert_eq!((segment.start, segment.end), (pair[0], pair[1]));
+            }
+            // No kinks where the segments meet.
+            for pair in segments.windows(2) {
+                let (end, start) = (pair[0].derivative(1.0), pair[1].derivative(0.0));
+                let sine = end.cross(start) / (end.len() * start.len());
+                assert!(
+                    sine.abs() < 1e-3 && end.dot(start) > 0.0,
+                    "{parameterization:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn full_tension_is_the_polygon() {
+        let points = zigzag();
+        let spline = CatmullRom::new(points.clone()).tension(1.0);
+        for (segment, pair) in spline.segments().iter().zip(points.windows(2)) {
+            assert!(close(segment.control1, pair[0]) && close(segment.control2, pair[1]));
+        }
+    }
+
+    #[test]
+    fn closed_catmull_rom_wraps_around() {
+        let points = zigzag();
+        let segments = CatmullRom::new(points.clone()).closed(true).segments();
+        assert_eq!(segments.len(), points.len());
+        let last = segments[segments.len() - 1];
+        assert_eq!((last.start, last.end), (points[4], points[0]));
+    }
+
+    #[test]
+    fn duplicate_points_stay_finite() {
+        let points = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+        ];
+        for segment in CatmullRom::new(points).segments() {
+            for point in [segment.control1, segment.control2] {
+                assert!(point.x.is_finite() && point.y.is_finite(), "{segment:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn too_few_points_give_nothing() {
+        let one = vec![Vec2::new(1.0, 1.0)];
+        assert!(CatmullRom::new(one.clone()).segments().is_empty());
+        assert!(BSpline::new(one).segments().is_empty());
+        let two = vec![Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)];
+        assert!(CatmullRom::new(two.clone())
+            .closed(true)
+            .segments()
+            .is_empty());
+        assert!(BSpline::new(two).closed(true).segments().is_empty());
+    }
+
+    #[test]
+    fn open_b_spline_is_clamped_to_its_ends() {
+        let points = zigzag();
+        let segments = BSpline::new(points.clone()).segments();
+        assert_eq!(segments.len(), points.len() + 1);
+        assert!(close(segments[0].start, points[0]));
+        assert!(close(segments[segments.len() - 1].end, points[4]));
+    }
+
+    #[test]
+    fn b_spline_is_smooth_at_the_joins() {
+        for spline in [BSpline::new(zigzag()), BSpline::new(zigzag()).closed(true)] {
+            let segments = spline.segments();
+            for pair in segments.windows(2) {
+                assert!(close(pair[0].end, pair[1].start));
+                assert!(close(pair[0].derivative(1.0), pair[1].derivative(0.0)));
+                assert!(close(
+                    pair[0].second_derivative(1.0),
+                    pair[1].second_derivative(0.0)
+                ));
+            }
+        }
+        let closed = BSpline::new(zigzag()).closed(true).segments();
+        assert_eq!(closed.len(), 5);
+        assert!(close(closed[4].end, closed[0].start));
+    }
+
+    #[test]
+    fn flattening_joins_the_segments() {
+        let spline = CatmullRom::new(zigzag());
+        let polyline = spline.flatten(0.5);
+        assert_eq!(polyline.first(), Some(&zigzag()[0]));
+        assert_eq!(polyline.last(), Some(&zigzag()[4]));
+        // Every point on the curve is passed, and the joins aren't doubled.
+        for point in zigzag() {
+            assert_eq!(polyline.iter().filter(|p| **p == point).count(), 1);
+        }
+    }
+}
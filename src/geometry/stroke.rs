@@ -0,0 +1,402 @@
+//! Lines of any width. A stroke is built from overlapping pieces wound the same way,
+//! a body per segment plus joins and caps, so it fills correctly with `FillRule::NonZero`.
+//! `outline` merges the pieces for when that isn't enough.
+
+use crate::geometry::polygon::{ring_signed_area, FillRule, Polygon};
+use crate::math::Vec2;
+
+/// Largest distance between a round join or cap and the true circle, in pixels.
+const ROUND_TOLERANCE: f32 = 0.25;
+
+/// How two segments are connected where a polyline turns.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Join {
+    /// Outer edges extended until they meet, falling back to `Bevel` past the miter limit.
+    #[default]
+    Miter,
+    Round,
+    /// Outer corners connected with a straight edge.
+    Bevel,
+}
+
+/// How the ends of an open polyline look.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Cap {
+    /// Ends exactly at the end point.
+    #[default]
+    Butt,
+    /// Goes on for half the width past the end point.
+    Square,
+    Round,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: Join,
+    pub cap: Cap,
+    /// Longest allowed miter, as a multiple of the width.
+    pub miter_limit: f32,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: Join::Miter,
+            cap: Cap::Butt,
+            miter_limit: 10.0,
+        }
+    }
+
+    pub fn join(self, join: Join) -> Self {
+        Self { join, ..self }
+    }
+
+    pub fn cap(self, cap: Cap) -> Self {
+        Self { cap, ..self }
+    }
+
+    pub fn miter_limit(self, miter_limit: f32) -> Self {
+        Self {
+            miter_limit,
+            ..self
+        }
+    }
+
+    pub fn line(&self, from: Vec2, to: Vec2) -> Polygon {
+        self.polyline(&[from, to], false)
+    }
+
+    /// Outline of a polyline, `closed` connects the last point back to the first with a join.
+    pub fn polyline(&self, points: &[Vec2], closed: bool) -> Polygon {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let half = self.width / 2.0;
+        let mut pieces = Pieces::default();
+        if half <= 0.0 {
+            return pieces.into_polygon();
+        }
+
+        match points[..] {
+            [] => {}
+            [point] => match self.cap {
+                Cap::Butt => {}
+                Cap::Square => {
+                    let corner = Vec2::new(half, half);
+                    pieces.push(vec![
+                        point - corner,
+                        point + Vec2::new(half, -half),
+                        point + corner,
+                        point + Vec2::new(-half, half),
+                    ]);
+                }
+                Cap::Round => pieces.push(circle(point, half)),
+            },
+            _ => {
+                let count = points.len();
+                let segments = if closed { count } else { count - 1 };
+                for i in 0..segments {
+                    let (from, to) = (points[i], points[(i + 1) % count]);
+                    let normal = (to - from).normalize().perp() * half;
+                    pieces.push(vec![from - normal, to - normal, to + normal, from + normal]);
+                }
+                let corners = if closed { 0..count } else { 1..count - 1 };
+                for i in corners {
+                    let previous = points[(i + count - 1) % count];
+                    let next = points[(i + 1) % count];
+                    self.push_join(&mut pieces, previous, points[i], next);
+                }
+                if !closed {
+                    self.push_cap(&mut pieces, points[1], points[0]);
+                    self.push_cap(&mut pieces, points[count - 2], points[count - 1]);
+                }
+            }
+        }
+        pieces.into_polygon()
+    }
+
+    /// Outline of every ring of the polygon.
+    pub fn polygon(&self, polygon: &Polygon) -> Polygon {
+        let mut outline = Polygon::default();
+        for ring in &polygon.rings {
+            outline.rings.extend(self.polyline(ring, true).rings);
+        }
+        outline
+    }
+
+    /// The stroked polyline as rings that don't overlap, so it reads the same with either fill
+    /// rule and can go into a boolean operation. Merging is quadratic in the number of edges,
+    /// `polyline` is enough for drawing.
+    pub fn outline(&self, points: &[Vec2], closed: bool) -> Polygon {
+        self.polyline(points, closed).simplified(FillRule::NonZero)
+    }
+
+    /// Pixels of the stroked polyline.
+    pub fn solid(&self, points: &[Vec2], closed: bool) -> Vec<Vec2> {
+        self.polyline(points, closed).solid_color(FillRule::NonZero)
+    }
+
+    /// Fills the gap on the outside of the turn at `corner`.
+    fn push_join(&self, pieces: &mut Pieces, previous: Vec2, corner: Vec2, next: Vec2) {
+        let half = self.width / 2.0;
+        let incoming = (corner - previous).normalize();
+        let outgoing = (next - corner).normalize();
+        let turn = incoming.cross(outgoing);
+        if self.join == Join::Round {
+            pieces.push(circle(corner, half));
+            return;
+        }
+        if turn == 0.0 && incoming.dot(outgoing) > 0.0 {
+            // Straight on, the bodies already meet.
+            return;
+        }
+        // The outer side is on the right of a left turn (y up) and the other way round.
+        let side = if turn > 0.0 { -half } else { half };
+        let (outer_in, outer_out) = (incoming.perp() * side, outgoing.perp() * side);
+        let bisector = (outer_in + outer_out).normalize();
+        // Miter length over width is 1 / cos of half the angle between the two offsets.
+        let cos_half = bisector.dot(outer_in) / half;
+        if self.join == Join::Miter && cos_half > 0.0 && 1.0 / cos_half <= self.miter_limit {
+            let tip = corner + bisector * (half / cos_half);
+            pieces.push(vec![corner, corner + outer_in, tip, corner + outer_out]);
+        } else {
+            pieces.push(vec![corner, corner + outer_in, corner + outer_out]);
+        }
+    }
+
+    /// Cap at `end`, the segment comes in from `before`.
+    fn push_cap(&self, pieces: &mut Pieces, before: Vec2, end: Vec2) {
+        let half = self.width / 2.0;
+        let direction = (end - before).normalize() * half;
+        let normal = direction.perp();
+        match self.cap {
+            Cap::Butt => {}
+            Cap::Square => pieces.push(vec![
+                end - normal,
+                end + direction - normal,
+                end + direction + normal,
+                end + normal,
+            ]),
+            Cap::Round => pieces.push(circle(end, half)),
+        }
+    }
+}
+
+/// Rings that all get wound the same way, so overlaps don't cancel out.
+#[derive(Default)]
+struct Pieces {
+    rings: Vec<Vec<Vec2>>,
+}
+
+impl Pieces {
+    fn push(&mut self, mut ring: Vec<Vec2>) {
+        let area = ring_signed_area(&ring);
+        if area == 0.0 {
+            return;
+        }
+        if area < 0.0 {
+            ring.reverse();
+        }
+        self.rings.push(ring);
+    }
+
+    fn into_polygon(self) -> Polygon {
+        Polygon::from_rings(self.rings)
+    }
+}
+
+/// Polygon close enough to the circle that the difference stays under `ROUND_TOLERANCE`.
+fn circle(center: Vec2, radius: f32) -> Vec<Vec2> {
+    let step = 2.0 * (1.0 - ROUND_TOLERANCE / radius).clamp(-1.0, 1.0).acos();
+    let count = (std::f32::consts::TAU / step).ceil().max(8.0) as usize;
+    (0..count)
+        .map(|i| {
+            center + Vec2::from_angle(std::f32::consts::TAU * i as f32 / count as f32) * radius
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Line;
+    use crate::random::Random;
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    fn corner() -> [Vec2; 3] {
+        [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+        ]
+    }
+
+    #[test]
+    fn caps_on_a_line() {
+        let (from, to) = (Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0));
+        let butt = Stroke::new(2.0).line(from, to);
+        assert!(close(butt.area(), 20.0, 1e-4));
+        assert!(butt.contains(Vec2::new(5.0, 0.9), FillRule::NonZero));
+        assert!(!butt.contains(Vec2::new(10.5, 0.0), FillRule::NonZero));
+
+        let square = Stroke::new(2.0)
+            .cap(Cap::Square)
+            .outline(&[from, to], false);
+        assert!(close(square.area(), 24.0, 1e-4));
+        assert!(square.contains(Vec2::new(10.5, 0.5), FillRule::NonZero));
+
+        let round = Stroke::new(2.0).cap(Cap::Round).outline(&[from, to], false);
+        // Both caps together make one whole circle polygon.
+        let expected = 20.0 + ring_signed_area(&circle(Vec2::default(), 1.0));
+        assert!(close(round.area(), expected, 1e-4), "{}", round.area());
+    }
+
+    #[test]
+    fn joins_on_a_right_angle() {
+        for (join, area) in [(Join::Miter, 40.0), (Join::Bevel, 39.5)] {
+            let outline = Stroke::new(2.0).join(join).outline(&corner(), false);
+            assert_eq!(outline.rings.len(), 1, "{join:?}");
+            assert!(
+                close(outline.area(), area, 1e-3),
+                "{join:?} {}",
+                outline.area()
+            );
+        }
+        let round = Stroke::new(2.0).join(Join::Round).outline(&corner(), false);
+        // The join sticks out by a quarter of its circle polygon.
+        let quarter = ring_signed_area(&circle(Vec2::default(), 1.0)) / 4.0;
+        assert!(
+            close(round.area(), 39.0 + quarter, 1e-4),
+            "{}",
+            round.area()
+        );
+    }
+
+    #[test]
+    fn sharp_miters_fall_back_to_bevels() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        let tip = Vec2::new(10.0 + 5.0, 0.5);
+        let long = Stroke::new(2.0).miter_limit(100.0).polyline(&points, false);
+        assert!(long.contains(tip, FillRule::NonZero));
+        let limited = Stroke::new(2.0).polyline(&points, false);
+        assert!(!limited.contains(tip, FillRule::NonZero));
+    }
+
+    #[test]
+    fn closed_rings_leave_a_hole() {
+        let ring = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        let outline = Stroke::new(2.0).outline(&ring, true);
+        assert_eq!(outline.rings.len(), 2);
+        assert!(close(outline.area(), 144.0 - 64.0, 1e-3));
+        assert!(!outline.contains(Vec2::new(5.0, 5.0), FillRule::NonZero));
+
+        let polygon = Polygon::new(ring[..4].to_vec());
+        assert_eq!(
+            Stroke::new(2.0).polygon(&polygon),
+            Stroke::new(2.0).polyline(&ring, true)
+        );
+    }
+
+    #[test]
+    fn round_stroke_covers_points_within_half_the_width() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(20.0, 5.0),
+            Vec2::new(8.0, 12.0),
+            Vec2::new(25.0, 20.0),
+        ];
+        let stroke = Stroke::new(6.0).join(Join::Round).cap(Cap::Round);
+        let pieces = stroke.polyline(&points, false);
+        let outline = stroke.outline(&points, false);
+        let random = Random::new(11);
+        for _ in 0..2000 {
+            let point = Vec2::new(
+                random.random_range(-5.0, 30.0),
+                random.random_range(-5.0, 25.0),
+            );
+            let distance = points
+                .windows(2)
+                .map(|pair| Line::new(pair[0], pair[1]).distance(point))
+                .fold(f32::INFINITY, f32::min);
+            if (distance - 3.0).abs() < ROUND_TOLERANCE + 0.01 {
+                continue;
+            }
+            let expected = distance < 3.0;
+            assert_eq!(
+                pieces.contains(point, FillRule::NonZero),
+                expected,
+                "{point:?}"
+            );
+            // Merged pieces read the same with either rule.
+            assert_eq!(
+                outline.contains(point, FillRule::NonZero),
+                expected,
+                "{point:?}"
+            );
+            assert_eq!(
+                outline.contains(point, FillRule::EvenOdd),
+                expected,
+                "{point:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn pieces_wind_the_same_way() {
+        let mut points = corner().to_vec();
+        points.push(Vec2::new(0.0, 10.0));
+        for join in [Join::Miter, Join::Round, Join::Bevel] {
+            let polygon = Stroke::new(3.0)
+                .join(join)
+                .cap(Cap::Square)
+                .polyline(&points, false);
+            assert!(polygon
+                .rings
+                .iter()
+                .all(|ring| ring_signed_area(ring) > 0.0));
+        }
+    }
+
+    #[test]
+    fn degenerate_input() {
+        assert!(Stroke::new(0.0)
+            .line(Vec2::default(), Vec2::new(1.0, 0.0))
+            .rings
+            .is_empty());
+        assert!(Stroke::new(2.0).polyline(&[], false).rings.is_empty());
+        let point = [Vec2::new(1.0, 1.0)];
+        assert!(Stroke::new(2.0).polyline(&point, false).rings.is_empty());
+        let dot = Stroke::new(2.0).cap(Cap::Square).polyline(&point, false);
+        assert!(close(dot.area(), 4.0, 1e-5));
+        // Repeated points don't produce bodies with no direction.
+        let repeated = [point[0], point[0], Vec2::new(5.0, 1.0)];
+        assert!(close(
+            Stroke::new(2.0).polyline(&repeated, false).area(),
+            8.0,
+            1e-5
+        ));
+    }
+}
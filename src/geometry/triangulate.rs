@@ -0,0 +1,303 @@
+//! Ear-clipping triangulation, holes are first bridged into their outer ring so
+//! every part becomes a single ring.
+
+use std::cmp::Ordering;
+
+use crate::geometry::polygon::{clean_ring, Polygon};
+use crate::geometry::Triangle;
+use crate::math::Vec2;
+use crate::predicates::orient2d;
+
+impl Polygon {
+    /// Triangles covering the polygon, wound counter-clockwise (y up) like `orient` winds outer rings.
+    /// Duplicate and collinear points are dropped first, they only add triangles without area.
+    pub fn triangulate(&self) -> Vec<Triangle> {
+        let mut triangles = Vec::new();
+        for part in self.parts() {
+            let mut rings = part.rings.iter().map(|ring| clean_ring(ring));
+            let Some(mut outer) = rings.next() else {
+                continue;
+            };
+            let mut holes: Vec<Vec<Vec2>> = rings.filter(|hole| hole.len() >= 3).collect();
+            // Right to left, so the bridges found earlier don't cross the later holes.
+            holes.sort_by(|a, b| rightmost(b).1.x.total_cmp(&rightmost(a).1.x));
+            for hole in holes {
+                bridge(&mut outer, &hole);
+            }
+            clip_ears(&outer, &mut triangles);
+        }
+        triangles
+    }
+}
+
+fn rightmost(ring: &[Vec2]) -> (usize, Vec2) {
+    ring.iter()
+        .copied()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.x.total_cmp(&b.x).then(b.y.total_cmp(&a.y)))
+        .expect("rings have points")
+}
+
+/// Joins the hole into the outer ring with a pair of overlapping edges between a vertex of
+/// each that can see the other (David Eberly's method).
+fn bridge(outer: &mut Vec<Vec2>, hole: &[Vec2]) {
+    let (hole_index, from) = rightmost(hole);
+    let Some(to) = visible_vertex(outer, from) else {
+        return;
+    };
+    let mut spliced = Vec::with_capacity(outer.len() + hole.len() + 2);
+    spliced.extend_from_slice(&outer[..=to]);
+    spliced.extend(hole[hole_index..].iter().chain(&hole[..hole_index]));
+    spliced.push(from);
+    spliced.extend_from_slice(&outer[to..]);
+    *outer = spliced;
+}
+
+/// Index of an outer vertex that can be joined to `point` without crossing any edge.
+#[allow(clippy::suboptimal_flops)]
+fn visible_vertex(outer: &[Vec2], point: Vec2) -> Option<usize> {
+    let count = outer.len();
+    // Closest edge hit by a ray from the point to the right.
+    let mut closest: Option<(f32, usize)> = None;
+    for i in 0..count {
+        let (a, b) = (outer[i], outer[(i + 1) % count]);
+        if a.y.min(b.y) > point.y || a.y.max(b.y) < point.y {
+            continue;
+        }
+        let span = b.y - a.y;
+        let x = if span == 0.0 {
+            a.x.min(b.x)
+        } else {
+            a.x + (point.y - a.y) / span * (b.x - a.x)
+        };
+        if x >= point.x && closest.is_none_or(|(closest_x, _)| x < closest_x) {
+            let end = if a.x > b.x { i } else { (i + 1) % count };
+            closest = Some((x, end));
+        }
+    }
+    let (x, candidate) = closest?;
+    let hit = Vec2::new(x, point.y);
+    if hit == outer[candidate] {
+        return Some(candidate);
+    }
+
+    // Vertices inside the triangle between the point, the hit and the candidate would block
+    // the view, the one closest in angle to the ray is visible.
+    let corner = outer[candidate];
+    let triangle = if corner.y < point.y {
+        [point, corner, hit]
+    } else {
+        [point, hit, corner]
+    };
+    let slope = |vertex: Vec2| {
+        let offset = vertex - point;
+        ((offset.y / offset.x).abs(), offset.length_squared())
+    };
+    let mut best = (candidate, slope(corner));
+    for (index, &vertex) in outer.iter().enumerate() {
+        if vertex == corner || !in_triangle_or_edge(vertex, triangle) {
+            continue;
+        }
+        let key = slope(vertex);
+        if key
+            .0
+            .total_cmp(&best.1 .0)
+            .then(key.1.total_cmp(&best.1 .1))
+            == Ordering::Less
+        {
+            best = (index, key);
+        }
+    }
+    // Earlier bridges repeat vertices, only one of the copies has `point` inside its corner.
+    let vertex = outer[best.0];
+    (0..count)
+        .filter(|index| outer[*index] == vertex)
+        .find(|index| in_corner(outer, *index, point))
+        .or(Some(best.0))
+}
+
+/// Whether `point` lies in the interior angle of the ring at `index`, the ring winds counter-clockwise (y up).
+fn in_corner(ring: &[Vec2], index: usize, point: Vec2) -> bool {
+    let count = ring.len();
+    let (previous, vertex, next) = (
+        ring[(index + count - 1) % count],
+        ring[index],
+        ring[(index + 1) % count],
+    );
+    let after_previous = orient2d(previous, vertex, point) == Ordering::Greater;
+    let before_next = orient2d(vertex, next, point) == Ordering::Greater;
+    if orient2d(previous, vertex, next) == Ordering::Less {
+        after_previous || before_next
+    } else {
+        after_previous && before_next
+    }
+}
+
+/// The triangle's corners are given counter-clockwise (y up).
+fn in_triangle_or_edge(point: Vec2, [a, b, c]: [Vec2; 3]) -> bool {
+    orient2d(a, b, point) != Ordering::Less
+        && orient2d(b, c, point) != Ordering::Less
+        && orient2d(c, a, point) != Ordering::Less
+}
+
+fn clip_ears(ring: &[Vec2], triangles: &mut Vec<Triangle>) {
+    let mut remaining: Vec<Vec2> = ring.to_vec();
+    let mut index = 0;
+    let mut attempts = 0;
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let (previous, current, next) = (
+            remaining[(index + count - 1) % count],
+            remaining[index],
+            remaining[(index + 1) % count],
+        );
+        let turn = orient2d(previous, current, next);
+        if turn == Ordering::Equal {
+            // Collinear after earlier clips, the point adds nothing.
+            remaining.remove(index);
+        } else if is_ear(&remaining, index) || (attempts > count && turn == Ordering::Greater) {
+            // After a fruitless round the ring self-intersects, any convex corner is clipped to get through.
+            triangles.push(Triangle::new(previous, current, next));
+            remaining.remove(index);
+            attempts = 0;
+        } else {
+            attempts += 1;
+            if attempts > 2 * count {
+                return;
+            }
+            index += 1;
+        }
+        index %= remaining.len().max(1);
+    }
+    if let [a, b, c] = remaining[..] {
+        if orient2d(a, b, c) != Ordering::Equal {
+            triangles.push(Triangle::new(a, b, c));
+        }
+    }
+}
+
+/// A convex corner without any other vertex of the ring inside it.
+fn is_ear(ring: &[Vec2], index: usize) -> bool {
+    let count = ring.len();
+    let corners = [
+        ring[(index + count - 1) % count],
+        ring[index],
+        ring[(index + 1) % count],
+    ];
+    if orient2d(corners[0], corners[1], corners[2]) != Ordering::Greater {
+        return false;
+    }
+    // Bridges repeat vertices, copies of the corners don't block the ear.
+    ring.iter()
+        .all(|point| corners.contains(point) || !in_triangle_or_edge(*point, corners))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::polygon::{ring_signed_area, FillRule};
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Vec2> {
+        vec![
+            Vec2::new(x, y),
+            Vec2::new(x + size, y),
+            Vec2::new(x + size, y + size),
+            Vec2::new(x, y + size),
+        ]
+    }
+
+    /// The triangles are counter-clockwise, don't overlap and cover exactly the polygon.
+    fn check_cover(polygon: &Polygon, triangles: &[Triangle]) {
+        let mut total = 0.0;
+        for triangle in triangles {
+            let area = ring_signed_area(&[triangle.a, triangle.b, triangle.c]);
+            assert!(area > 0.0, "{triangle:?}");
+            total += area;
+            let center = (triangle.a + triangle.b + triangle.c) / 3.0;
+            assert!(polygon.contains(center, FillRule::EvenOdd), "{triangle:?}");
+        }
+        assert!((total - polygon.area()).abs() < 1e-3, "{total}");
+    }
+
+    #[test]
+    fn convex_polygon_gives_n_minus_2_triangles() {
+        let ring: Vec<Vec2> = (0..12)
+            .map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU / 12.0) * 10.0)
+            .collect();
+        let polygon = Polygon::new(ring);
+        let triangles = polygon.triangulate();
+        assert_eq!(triangles.len(), 10);
+        check_cover(&polygon, &triangles);
+    }
+
+    #[test]
+    fn concave_and_clockwise_rings() {
+        // A comb with three teeth, given in screen order.
+        let mut ring = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(7.0, 0.0),
+            Vec2::new(7.0, 5.0),
+            Vec2::new(6.0, 5.0),
+            Vec2::new(6.0, 1.0),
+            Vec2::new(4.0, 1.0),
+            Vec2::new(4.0, 5.0),
+            Vec2::new(3.0, 5.0),
+            Vec2::new(3.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 5.0),
+            Vec2::new(0.0, 5.0),
+        ];
+        ring.reverse();
+        let polygon = Polygon::new(ring);
+        let triangles = polygon.triangulate();
+        check_cover(&polygon, &triangles);
+        // Clipping a tooth can leave points on a straight line, those go without a triangle.
+        assert!(triangles.len() <= 10, "{}", triangles.len());
+    }
+
+    #[test]
+    fn holes_are_bridged() {
+        let polygon = Polygon::new(square(0.0, 0.0, 10.0))
+            .with_hole(square(1.0, 1.0, 3.0))
+            .with_hole(square(6.0, 2.0, 2.0))
+            .with_hole(square(2.0, 6.0, 3.0));
+        let triangles = polygon.triangulate();
+        check_cover(&polygon, &triangles);
+        // Each hole adds its own points plus the two ends of the bridge, less any left on a straight line.
+        assert!(
+            triangles.len() <= 4 + 3 * (4 + 2) - 2,
+            "{}",
+            triangles.len()
+        );
+    }
+
+    #[test]
+    fn hole_at_the_same_height_as_an_outer_vertex() {
+        let outer = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 4.0),
+            Vec2::new(6.0, 5.0),
+            Vec2::new(10.0, 6.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let polygon = Polygon::new(outer).with_hole(square(2.0, 4.0, 2.0));
+        check_cover(&polygon, &polygon.triangulate());
+    }
+
+    #[test]
+    fn islands_and_degenerate_points() {
+        let mut outer = square(0.0, 0.0, 9.0);
+        // A repeated point and a point in the middle of an edge.
+        outer.insert(1, Vec2::new(0.0, 0.0));
+        outer.insert(2, Vec2::new(4.5, 0.0));
+        let polygon = Polygon::new(outer)
+            .with_hole(square(2.0, 2.0, 5.0))
+            .with_hole(square(3.0, 3.0, 3.0));
+        let triangles = polygon.triangulate();
+        assert_eq!(triangles.len(), (4 + 4 + 2 - 2) + (4 - 2));
+        check_cover(&polygon, &triangles);
+        assert!(Polygon::default().triangulate().is_empty());
+    }
+}
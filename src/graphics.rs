@@ -1,16 +1,29 @@
-use crate::{geometry::Line, math::Vec2};
+//! Functions here produce pixels, analytical shapes are in `geometry`
 
-/// Functions here produce pixels, analytical shapes are in `geometry`
+use crate::font::{glyph, GLYPH_WIDTH};
+use crate::geometry::{Circle, Line};
+use crate::math::Vec2;
 
 pub fn circle(origin: Vec2, radius: f32) -> Vec<Vec2> {
-    let surface = (radius * std::f32::consts::TAU).ceil();
-    let mut points: Vec<Vec2> = Vec::with_capacity(surface as usize);
-    for i in 0..surface as usize {
-        points.push(origin + Vec2::from_angle(std::f32::consts::TAU / surface * i as f32) * radius);
-    }
-    points
+    Circle::new(origin, radius).empty()
 }
 
 pub fn line(from: Vec2, to: Vec2) -> Vec<Vec2> {
     Line::new(from, to).solid()
 }
+
+/// Pixels of `text` written with the built-in 3x5 font, `origin` is the top left corner.
+pub fn text(origin: Vec2, text: &str) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let glyph_x = origin.x + (i as u32 * (GLYPH_WIDTH + 1)) as f32;
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    points.push(Vec2::new(glyph_x + column as f32, origin.y + row as f32));
+                }
+            }
+        }
+    }
+    points
+}
@@ -0,0 +1,527 @@
+//! Parameter panel drawn straight into the framebuffer, on top of the sketch.
+
+use simple_pixels::{rgb::RGBA8, Context, MouseButton};
+
+use crate::font::GLYPH_HEIGHT;
+use crate::graphics::text;
+use crate::math::Vec2;
+use crate::params::{Param, Params, Value};
+
+const PANEL_WIDTH: i32 = 180;
+const LABEL_WIDTH: i32 = 76;
+const ROW_HEIGHT: i32 = 10;
+const PICKER_HEIGHT: i32 = ROW_HEIGHT * 4;
+const PADDING: i32 = 4;
+
+const BACKGROUND: RGBA8 = RGBA8::new(30, 30, 36, 255);
+const TRACK: RGBA8 = RGBA8::new(60, 60, 70, 255);
+const ACCENT: RGBA8 = RGBA8::new(70, 130, 180, 255);
+const TEXT: RGBA8 = RGBA8::new(220, 220, 220, 255);
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Widget {
+    Slider { param: usize, channel: usize },
+    Checkbox { param: usize },
+    Swatch { param: usize },
+    SaturationValue { param: usize },
+    Hue { param: usize },
+    Button { index: usize },
+}
+
+struct Row {
+    widget: Widget,
+    label: String,
+    y: i32,
+    height: i32,
+}
+
+pub struct Gui {
+    pub visible: bool,
+    origin: (i32, i32),
+    buttons: Vec<String>,
+    active: Option<Widget>,
+    /// Hue, saturation and value of the last picked color, the hue of a gray can't be
+    /// told from its channels.
+    picked: Option<(usize, [f32; 3])>,
+    mouse_was_down: bool,
+}
+
+impl Gui {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            visible: false,
+            origin: (x, y),
+            buttons: Vec::new(),
+            active: None,
+            picked: None,
+            mouse_was_down: false,
+        }
+    }
+
+    pub fn add_button(&mut self, name: &str) -> &mut Self {
+        self.buttons.push(name.to_string());
+        self
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Applies mouse input to the parameters, returns the name of a button if one was clicked.
+    pub fn update(&mut self, ctx: &Context, params: &mut Params) -> Option<String> {
+        let down = ctx.is_mouse_button_down(MouseButton::Left);
+        let pressed = down && !self.mouse_was_down;
+        self.mouse_was_down = down;
+        if !self.visible || !down {
+            self.active = None;
+            return None;
+        }
+
+        let (mouse_x, mouse_y) = ctx.get_mouse_pos_int();
+        if pressed {
+            let (x, _) = self.origin;
+            let hovered = self.rows(params).into_iter().find(|row| {
+                (x..x + PANEL_WIDTH).contains(&mouse_x)
+                    && (row.y..row.y + row.height).contains(&mouse_y)
+            });
+            match hovered.map(|row| row.widget) {
+                Some(Widget::Checkbox { param }) => {
+                    if let Some(Param {
+                        value: Value::Bool(value),
+                        ..
+                    }) = params.iter_mut().nth(param)
+                    {
+                        *value = !*value;
+                    }
+                }
+                Some(Widget::Button { index }) => return self.buttons.get(index).cloned(),
+                widget => self.active = widget,
+            }
+        }
+
+        let (track_x, track_width) = self.track();
+        let across = ((mouse_x - track_x) as f32 / track_width as f32).clamp(0.0, 1.0);
+        match self.active {
+            Some(Widget::Slider { param, channel }) => {
+                if let Some(param) = params.iter_mut().nth(param) {
+                    set_slider(param, channel, across);
+                }
+            }
+            Some(widget @ (Widget::SaturationValue { param } | Widget::Hue { param })) => {
+                let rows = self.rows(params);
+                let row = rows.iter().find(|row| row.widget == widget);
+                let (control_y, control_height) = control(row.expect("rows match params"));
+                let down = ((mouse_y - control_y) as f32 / control_height as f32).clamp(0.0, 1.0);
+                if let Some(Param {
+                    value: Value::Color(color),
+                    ..
+                }) = params.iter_mut().nth(param)
+                {
+                    let [hue, saturation, value] = self.hsv(param, *color);
+                    let hsv = if matches!(widget, Widget::Hue { .. }) {
+                        [across * 360.0, saturation, value]
+                    } else {
+                        [hue, across, 1.0 - down]
+                    };
+                    *color = RGBA8 {
+                        a: color.a,
+                        ..hsv_to_rgb(hsv)
+                    };
+                    self.picked = Some((param, hsv));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn draw(&self, ctx: &mut Context, params: &Params) {
+        if !self.visible {
+            return;
+        }
+        let (x, y) = self.origin;
+        let rows = self.rows(params);
+        let bottom = rows.last().map_or(y + PADDING, |row| row.y + row.height);
+        fill(ctx, x, y, PANEL_WIDTH, bottom + PADDING - y, BACKGROUND);
+
+        let (track_x, track_width) = self.track();
+        let text_offset = (ROW_HEIGHT - GLYPH_HEIGHT as i32) / 2;
+        for row in &rows {
+            let label_origin = Vec2::new((x + PADDING) as f32, (row.y + text_offset) as f32);
+            let (control_y, control_height) = control(row);
+            match row.widget {
+                Widget::Slider { param, channel } => {
+                    let param = params.iter().nth(param).expect("rows match params");
+                    let filled = (slider_fraction(param, channel) * track_width as f32) as i32;
+                    fill(ctx, track_x, control_y, track_width, control_height, TRACK);
+                    fill(ctx, track_x, control_y, filled, control_height, ACCENT);
+                    let value = Vec2::new((track_x + 2) as f32, (row.y + text_offset) as f32);
+                    plot(ctx, &text(value, &slider_text(param, channel)), TEXT);
+                }
+                Widget::Checkbox { param } => {
+                    let checked = matches!(
+                        params.iter().nth(param).map(|param| param.value),
+                        Some(Value::Bool(true))
+                    );
+                    fill(
+                        ctx,
+                        track_x,
+                        control_y,
+                        control_height,
+                        control_height,
+                        TRACK,
+                    );
+                    if checked {
+                        let inner = control_height - 4;
+                        fill(ctx, track_x + 2, control_y + 2, inner, inner, ACCENT);
+                    }
+                }
+                Widget::Swatch { param } => {
+                    if let Some(Value::Color(color)) = params.iter().nth(param).map(|p| p.value) {
+                        fill(ctx, track_x, control_y, track_width, control_height, color);
+                    }
+                }
+                Widget::SaturationValue { param } => {
+                    if let Some(Value::Color(color)) = params.iter().nth(param).map(|p| p.value) {
+                        let [hue, saturation, value] = self.hsv(param, color);
+                        for down in 0..control_height {
+                            for across in 0..track_width {
+                                let shade = hsv_to_rgb([
+                                    hue,
+                                    across as f32 / (track_width - 1) as f32,
+                                    1.0 - down as f32 / (control_height - 1) as f32,
+                                ]);
+                                fill(ctx, track_x + across, control_y + down, 1, 1, shade);
+                            }
+                        }
+                        let marker_x = track_x + (saturation * (track_width - 1) as f32) as i32;
+                        let marker_y =
+                            control_y + ((1.0 - value) * (control_height - 1) as f32) as i32;
+                        let marker = if value > 0.5 { BACKGROUND } else { TEXT };
+                        fill(ctx, marker_x - 1, marker_y - 1, 3, 3, marker);
+                    }
+                }
+                Widget::Hue { param } => {
+                    if let Some(Value::Color(color)) = params.iter().nth(param).map(|p| p.value) {
+                        for across in 0..track_width {
+                            let hue = across as f32 / (track_width - 1) as f32 * 360.0;
+                            let shade = hsv_to_rgb([hue, 1.0, 1.0]);
+                            fill(ctx, track_x + across, control_y, 1, control_height, shade);
+                        }
+                        let [hue, _, _] = self.hsv(param, color);
+                        let marker_x = track_x + (hue / 360.0 * (track_width - 1) as f32) as i32;
+                        fill(ctx, marker_x - 1, control_y, 2, control_height, BACKGROUND);
+                    }
+                }
+                Widget::Button { .. } => {
+                    let button_width = PANEL_WIDTH - PADDING * 2;
+                    fill(
+                        ctx,
+                        x + PADDING,
+                        control_y,
+                        button_width,
+                        control_height,
+                        TRACK,
+                    );
+                    let label = Vec2::new((x + PADDING * 2) as f32, label_origin.y);
+                    plot(ctx, &text(label, &row.label), TEXT);
+                    continue;
+                }
+            }
+            plot(ctx, &text(label_origin, &row.label), TEXT);
+        }
+    }
+
+    /// The color's hue, saturation and value, as last picked if it hasn't changed since.
+    fn hsv(&self, param: usize, color: RGBA8) -> [f32; 3] {
+        match self.picked {
+            Some((picked, hsv))
+                if picked == param && channels(hsv_to_rgb(hsv)) == channels(color) =>
+            {
+                hsv
+            }
+            _ => rgb_to_hsv(color),
+        }
+    }
+
+    /// Horizontal position and width of the controls column.
+    fn track(&self) -> (i32, i32) {
+        let (x, _) = self.origin;
+        (
+            x + PADDING + LABEL_WIDTH,
+            PANEL_WIDTH - LABEL_WIDTH - PADDING * 2,
+        )
+    }
+
+    fn rows(&self, params: &Params) -> Vec<Row> {
+        let mut widgets: Vec<(Widget, String, i32)> = Vec::new();
+        for (index, param) in params.iter().enumerate() {
+            let name = param.name.clone();
+            match param.value {
+                Value::Float(_) | Value::Int(_) => {
+                    let slider = Widget::Slider {
+                        param: index,
+                        channel: 0,
+                    };
+                    widgets.push((slider, name, ROW_HEIGHT));
+                }
+                Value::Bool(_) => {
+                    widgets.push((Widget::Checkbox { param: index }, name, ROW_HEIGHT));
+                }
+                Value::Color(_) => {
+                    widgets.push((Widget::Swatch { param: index }, name, ROW_HEIGHT));
+                    // Saturation across and value down, for the color's hue.
+                    let picker = Widget::SaturationValue { param: index };
+                    widgets.push((picker, " s/v".to_string(), PICKER_HEIGHT));
+                    widgets.push((Widget::Hue { param: index }, " hue".to_string(), ROW_HEIGHT));
+                }
+                Value::Vec2(_) => {
+                    for (channel, axis) in ["x", "y"].into_iter().enumerate() {
+                        let slider = Widget::Slider {
+                            param: index,
+                            channel,
+                        };
+                        widgets.push((slider, format!("{name}.{axis}"), ROW_HEIGHT));
+                    }
+                }
+            }
+        }
+        for (index, name) in self.buttons.iter().enumerate() {
+            widgets.push((Widget::Button { index }, name.clone(), ROW_HEIGHT));
+        }
+
+        let (_, mut y) = self.origin;
+        y += PADDING;
+        widgets
+            .into_iter()
+            .map(|(widget, label, height)| {
+                let row = Row {
+                    widget,
+                    label,
+                    y,
+                    height,
+                };
+                y += height;
+                row
+            })
+            .collect()
+    }
+}
+
+/// Top and height of a row's control, a pixel inside of the row on either side.
+fn control(row: &Row) -> (i32, i32) {
+    (row.y + 1, row.height - 2)
+}
+
+/// Where the value sits between `min` and `max`, 0 when there's no room between them.
+fn slider_fraction(param: &Param, channel: usize) -> f32 {
+    let span = param.max - param.min;
+    if span == 0.0 {
+        return 0.0;
+    }
+    let range = |value: f32| (value - param.min) / span;
+    match param.value {
+        Value::Float(value) => range(value),
+        Value::Int(value) => range(value as f32),
+        Value::Vec2(Vec2 { x, y }) => range(if channel == 0 { x } else { y }),
+        Value::Bool(_) | Value::Color(_) => 0.0,
+    }
+    .clamp(0.0, 1.0)
+}
+
+/// Leaves the value alone when `min` and `max` are the same, there's nothing to slide over.
+#[allow(clippy::suboptimal_flops)]
+fn set_slider(param: &mut Param, channel: usize, fraction: f32) {
+    let span = param.max - param.min;
+    if span == 0.0 {
+        return;
+    }
+    let value = param.min + span * fraction;
+    match &mut param.value {
+        Value::Float(current) => *current = value,
+        Value::Int(current) => *current = value.round() as i32,
+        Value::Vec2(vector) => {
+            if channel == 0 {
+                vector.x = value;
+            } else {
+                vector.y = value;
+            }
+        }
+        Value::Bool(_) | Value::Color(_) => {}
+    }
+}
+
+fn slider_text(param: &Param, channel: usize) -> String {
+    match param.value {
+        Value::Float(value) => format!("{value:.3}"),
+        Value::Int(value) => value.to_string(),
+        Value::Vec2(Vec2 { x, y }) => format!("{:.2}", if channel == 0 { x } else { y }),
+        value @ (Value::Bool(_) | Value::Color(_)) => value.to_string(),
+    }
+}
+
+fn channels(color: RGBA8) -> [u8; 3] {
+    [color.r, color.g, color.b]
+}
+
+/// Hue in degrees, saturation and value in [0, 1].
+fn rgb_to_hsv(color: RGBA8) -> [f32; 3] {
+    let [r, g, b] = channels(color).map(|channel| f32::from(channel) / 255.0);
+    let largest = color.r.max(color.g).max(color.b);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let delta = max - min;
+    let hue = if delta == 0.0 {
+        0.0
+    } else if largest == color.r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if largest == color.g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { delta / max };
+    [hue, saturation, max]
+}
+
+fn hsv_to_rgb([hue, saturation, value]: [f32; 3]) -> RGBA8 {
+    let channel = |offset: f32| {
+        let k = (offset + hue / 60.0).rem_euclid(6.0);
+        let level = value * saturation.mul_add(-k.min(4.0 - k).clamp(0.0, 1.0), 1.0);
+        (level * 255.0).round() as u8
+    };
+    RGBA8::new(channel(5.0), channel(3.0), channel(1.0), 255)
+}
+
+fn fill(ctx: &mut Context, x: i32, y: i32, width: i32, height: i32, color: RGBA8) {
+    let (from_x, from_y) = (x.max(0), y.max(0));
+    let to_x = (x + width).min(ctx.width() as i32);
+    let to_y = (y + height).min(ctx.height() as i32);
+    if from_x < to_x && from_y < to_y {
+        ctx.draw_rect(
+            from_x,
+            from_y,
+            (to_x - from_x) as u32,
+            (to_y - from_y) as u32,
+            color,
+        );
+    }
+}
+
+fn plot(ctx: &mut Context, points: &[Vec2], color: RGBA8) {
+    let (width, height) = (ctx.width() as i32, ctx.height() as i32);
+    for point in points {
+        let (x, y) = (point.x as i32, point.y as i32);
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            ctx.draw_pixel(x, y, color);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn float(value: f32, min: f32, max: f32) -> Param {
+        Param {
+            name: "value".to_string(),
+            value: Value::Float(value),
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn hsv_round_trips_every_channel_step() {
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(15) {
+                for b in (0..=255).step_by(15) {
+                    let color = RGBA8::new(r, g, b, 255);
+                    assert_eq!(hsv_to_rgb(rgb_to_hsv(color)), color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hsv_of_primaries() {
+        assert_eq!(rgb_to_hsv(RGBA8::new(255, 0, 0, 255)), [0.0, 1.0, 1.0]);
+        assert_eq!(rgb_to_hsv(RGBA8::new(0, 255, 0, 255)), [120.0, 1.0, 1.0]);
+        assert_eq!(rgb_to_hsv(RGBA8::new(0, 0, 255, 255)), [240.0, 1.0, 1.0]);
+        assert_eq!(rgb_to_hsv(RGBA8::new(0, 0, 0, 255)), [0.0, 0.0, 0.0]);
+        assert_eq!(hsv_to_rgb([360.0, 1.0, 1.0]), RGBA8::new(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn picked_hue_survives_gray() {
+        let mut gui = Gui::new(0, 0);
+        let gray = hsv_to_rgb([200.0, 0.0, 0.5]);
+        gui.picked = Some((0, [200.0, 0.0, 0.5]));
+        assert_eq!(gui.hsv(0, gray)[0], 200.0);
+        // Another color, or another parameter, is read from the rgb value.
+        assert_eq!(gui.hsv(1, gray)[0], 0.0);
+        assert_eq!(gui.hsv(0, RGBA8::new(0, 255, 0, 255))[0], 120.0);
+    }
+
+    #[test]
+    fn sliders_map_the_range() {
+        let mut param = float(2.5, 0.0, 10.0);
+        assert_eq!(slider_fraction(&param, 0), 0.25);
+        set_slider(&mut param, 0, 0.5);
+        assert_eq!(param.value, Value::Float(5.0));
+
+        let mut param = Param {
+            value: Value::Int(0),
+            ..float(0.0, -3.0, 3.0)
+        };
+        set_slider(&mut param, 0, 0.9);
+        assert_eq!(param.value, Value::Int(2));
+
+        let mut param = Param {
+            value: Value::Vec2(Vec2::new(0.0, 0.0)),
+            ..float(0.0, 0.0, 4.0)
+        };
+        set_slider(&mut param, 1, 0.25);
+        assert_eq!(param.value, Value::Vec2(Vec2::new(0.0, 1.0)));
+        assert_eq!(slider_fraction(&param, 1), 0.25);
+    }
+
+    #[test]
+    fn empty_range_leaves_the_value_alone() {
+        let mut param = float(3.0, 3.0, 3.0);
+        assert_eq!(slider_fraction(&param, 0), 0.0);
+        set_slider(&mut param, 0, 0.7);
+        assert_eq!(param.value, Value::Float(3.0));
+    }
+
+    #[test]
+    fn colors_get_a_swatch_picker_and_hue_row() {
+        let mut params = Params::new();
+        params
+            .add_float("speed", 1.0, 0.0, 2.0)
+            .add_color("tint", RGBA8::new(1, 2, 3, 255));
+        let mut gui = Gui::new(0, 0);
+        gui.add_button("reset");
+        let rows = gui.rows(&params);
+        let heights: Vec<i32> = rows.iter().map(|row| row.height).collect();
+        assert_eq!(
+            heights,
+            [
+                ROW_HEIGHT,
+                ROW_HEIGHT,
+                PICKER_HEIGHT,
+                ROW_HEIGHT,
+                ROW_HEIGHT
+            ]
+        );
+        assert!(matches!(
+            rows[2].widget,
+            Widget::SaturationValue { param: 1 }
+        ));
+        assert!(matches!(rows[4].widget, Widget::Button { index: 0 }));
+        // Rows follow each other without gaps.
+        for pair in rows.windows(2) {
+            assert_eq!(pair[0].y + pair[0].height, pair[1].y);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::HashSet;
+
+use simple_pixels::{Context, KeyCode};
+
+/// Remembers which keys were held on the previous update, so single presses can be told apart from held keys.
+#[derive(Default)]
+pub struct Keys {
+    held: HashSet<KeyCode>,
+}
+
+impl Keys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True only on the first update the key is down.
+    pub fn pressed(&mut self, ctx: &Context, key: KeyCode) -> bool {
+        if ctx.is_key_down(key) {
+            self.held.insert(key)
+        } else {
+            self.held.remove(&key);
+            false
+        }
+    }
+}
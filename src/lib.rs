@@ -0,0 +1,41 @@
+//! Toolkit for the sketch in `main.rs`: drawing, geometry, noise, parameters and the rest.
+//! It's a library so the parts a sketch doesn't use yet still count as used.
+#![warn(clippy::nursery, clippy::pedantic)]
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::missing_const_for_fn,
+    clippy::cast_possible_wrap,
+    clippy::missing_panics_doc
+)]
+// Only the sketch uses this, lints for the API of a published crate don't apply.
+#![allow(
+    clippy::missing_errors_doc,
+    clippy::must_use_candidate,
+    clippy::new_without_default,
+    clippy::return_self_not_must_use,
+    clippy::too_long_first_doc_paragraph
+)]
+pub mod canvas;
+pub mod capture;
+pub mod cli;
+pub mod clock;
+pub mod common;
+pub mod easing;
+pub mod fixed;
+pub mod font;
+pub mod geometry;
+pub mod graphics;
+pub mod gui;
+pub mod input;
+pub mod math;
+pub mod noise;
+pub mod params;
+pub mod poisson;
+pub mod ppt;
+pub mod predicates;
+pub mod random;
+pub mod spatial;
+pub mod sprite;
+pub mod tween;
@@ -7,41 +7,58 @@
     clippy::cast_possible_wrap,
     clippy::missing_panics_doc
 )]
-use common::{constrain, lerp_u8};
-use geometry::{Line, Triangle};
-use graphics::circle;
-use math::Vec2;
-use simple_pixels::{rgb::RGBA8, start, Config, Context, KeyCode, State};
-
-mod cli;
-mod clock;
-mod common;
-mod geometry;
-mod graphics;
-mod math;
-mod ppt;
-mod sprite;
+use std::io;
+use std::path::PathBuf;
 
-use clock::Clock;
+use miniquad_p5::canvas::{Background, Canvas};
+use miniquad_p5::capture::{frame_path, save_frame};
+use miniquad_p5::cli::Arguments;
+use miniquad_p5::clock::Clock;
+use miniquad_p5::common::{constrain, lerp_u8};
+use miniquad_p5::geometry::{Line, Triangle};
+use miniquad_p5::gui::Gui;
+use miniquad_p5::input::Keys;
+use miniquad_p5::math::{Affine2, Vec2};
+use miniquad_p5::params::{Params, ParamsFile};
+use miniquad_p5::random::Random;
+use miniquad_p5::sprite::Sprite;
+use simple_pixels::{rgb::RGBA8, start, Config, Context, KeyCode, State};
 
-use sprite::Sprite;
+const SKETCH_NAME: &str = "game";
+const SAVE_FRAME_PATTERN: &str = "####.ppm";
+const PARAMS_PATH: &str = "params.txt";
+const BLACK: RGBA8 = RGBA8::new(0, 0, 0, 255);
 
 fn main() {
     let (width, height) = (400, 400);
     let config = Config {
-        window_title: "game".to_string(),
+        window_title: SKETCH_NAME.to_string(),
         window_width: width,
         window_height: height,
         fullscreen: false,
         icon: None,
     };
 
-    let game = Game::new(width, height);
+    let arguments = Arguments::new();
+    let seed = arguments.named.get("seed").map_or_else(
+        || fastrand::u64(..),
+        |seed| seed.parse().expect("--seed should be a positive integer"),
+    );
+    println!("seed: {seed}");
+
+    let game = Game::new(width, height, seed);
     start(config, game);
 }
 
 struct Game {
     clock: Clock,
+    keys: Keys,
+    random: Random,
+    save_requested: bool,
+    params: Params,
+    gui: Gui,
+    canvas: Canvas,
+    params_file: ParamsFile,
     mouse_pos: Vec2,
     width: u32,
     height: u32,
@@ -51,15 +68,29 @@ struct Game {
 }
 
 impl Game {
-    pub fn new(width: u32, height: u32) -> Self {
+    pub fn new(width: u32, height: u32, seed: u64) -> Self {
+        // Seed the global generator too, so plain `fastrand` calls are reproducible as well.
+        fastrand::seed(seed);
+        let random = Random::new(seed);
         let clock = Clock::new();
         let mouse_pos = Vec2::new(0.0, 0.0);
-        let mut sprites: Vec<Sprite> = Vec::new();
+        let sprites: Vec<Sprite> = Vec::new();
         // sprites.push(Sprite::from_vec2(
         //     circle(Vec2::new(0.0, 0.0), 15.0), //mouse_pos + Vec2::new(25.0, 25.0)),
         //     RGBA8::new(20, 200, 100, 255),
         // ));
 
+        let mut params = Params::new();
+        params
+            .add_float("rotation_speed", 0.04, -0.2, 0.2)
+            .add_bool("dithered", false)
+            .add_bool("trails", false)
+            .add_float("fade_rate", 0.1, 0.0, 1.0)
+            .add_color("start_color", RGBA8::new(255, 0, 0, 255))
+            .add_color("end_color", RGBA8::new(0, 0, 255, 255));
+        let mut gui = Gui::new(4, 4);
+        gui.add_button("reset angle").add_button("export");
+
         let triangle = Triangle::new(
             Vec2::from_angle(0.0_f32.to_radians()) * 100.0,
             Vec2::from_angle(120.0_f32.to_radians()) * 100.0,
@@ -67,6 +98,13 @@ impl Game {
         );
         Self {
             clock,
+            keys: Keys::new(),
+            random,
+            save_requested: false,
+            params,
+            gui,
+            params_file: ParamsFile::new(PARAMS_PATH),
+            canvas: Canvas::new(width, height, Background::Clear(BLACK)),
             mouse_pos,
             width,
             height,
@@ -75,6 +113,13 @@ impl Game {
             angle: 0.0,
         }
     }
+
+    /// Saves the canvas, see `capture::frame_path` for the pattern syntax.
+    pub fn save_frame(&self, pattern: &str) -> Result<PathBuf, io::Error> {
+        let path = frame_path(pattern, SKETCH_NAME, self.random.seed(), self.clock.frame());
+        save_frame(&self.canvas, &path)?;
+        Ok(path)
+    }
 }
 
 impl State for Game {
@@ -82,6 +127,21 @@ impl State for Game {
         if ctx.is_key_down(KeyCode::Escape) {
             ctx.quit();
         }
+        if self.keys.pressed(ctx, KeyCode::S) {
+            self.save_requested = true;
+        }
+        if self.keys.pressed(ctx, KeyCode::G) {
+            self.gui.toggle();
+        }
+        self.params_file.poll(&mut self.params);
+        match self.gui.update(ctx, &mut self.params).as_deref() {
+            Some("reset angle") => self.angle = 0.0,
+            Some("export") => match self.params.export(PARAMS_PATH) {
+                Ok(()) => println!("exported parameters to {PARAMS_PATH}"),
+                Err(err) => eprintln!("couldn't export parameters: {err}"),
+            },
+            _ => {}
+        }
 
         let mouse = ctx.get_mouse_pos();
         self.mouse_pos = Vec2::new(
@@ -89,7 +149,15 @@ impl State for Game {
             constrain(mouse.1, 0.0, self.height as f32),
         );
 
-        self.angle += 0.04;
+        self.angle += self.params.float("rotation_speed");
+        self.canvas.background = if self.params.bool("trails") {
+            Background::Fade {
+                color: BLACK,
+                rate: self.params.float("fade_rate"),
+            }
+        } else {
+            Background::Clear(BLACK)
+        };
 
         //self.sprites[0].origin = self.mouse_pos;
 
@@ -97,17 +165,14 @@ impl State for Game {
     }
 
     fn draw(&mut self, ctx: &mut Context) {
-        ctx.clear();
+        self.canvas.begin_frame();
         for sprite in &self.sprites {
-            sprite.draw(ctx);
+            sprite.draw(&mut self.canvas);
         }
 
         let center = Vec2::new((self.width / 2) as f32, (self.height / 2) as f32);
-        let triangle = Triangle::new(
-            center + self.triangle.a.rotate(self.angle),
-            center + self.triangle.b.rotate(self.angle),
-            center + self.triangle.c.rotate(self.angle),
-        );
+        let transform = Affine2::from_translation(center) * Affine2::from_rotation(self.angle);
+        let triangle = self.triangle.transform(&transform);
 
         let points: Vec<Vec2> = triangle
             .solid_color()
@@ -118,12 +183,26 @@ impl State for Game {
         let shading = Shading::new(
             Vec2::new(0.0, 0.0),
             Vec2::new(self.width as f32, self.height as f32),
-            RGBA8::new(255, 0, 0, 255),
-            RGBA8::new(0, 0, 255, 255),
+            self.params.color("start_color"),
+            self.params.color("end_color"),
         );
 
-        shading.draw_shaded(ctx, &points);
-        //shading.draw_dithered(ctx, &points);
+        if self.params.bool("dithered") {
+            shading.draw_dithered(&mut self.canvas, &points);
+        } else {
+            shading.draw_shaded(&mut self.canvas, &points);
+        }
+
+        if self.save_requested {
+            self.save_requested = false;
+            match self.save_frame(SAVE_FRAME_PATTERN) {
+                Ok(path) => println!("saved frame to {}", path.display()),
+                Err(err) => eprintln!("couldn't save frame: {err}"),
+            }
+        }
+
+        self.canvas.present(ctx);
+        self.gui.draw(ctx, &self.params);
     }
 }
 
@@ -143,22 +222,22 @@ impl Shading {
         }
     }
 
-    pub fn draw_dithered(&self, ctx: &mut Context, points: &[Vec2]) {
+    pub fn draw_dithered(&self, canvas: &mut Canvas, points: &[Vec2]) {
         for point in points {
             let (x, y) = (point.x as i32, point.y as i32);
             let mix = self.line.todo_name(*point);
-            ctx.draw_pixel(x, y, dither(x, y, self.start_color, self.end_color, mix))
+            canvas.draw_pixel(x, y, dither(x, y, self.start_color, self.end_color, mix));
         }
     }
 
-    pub fn draw_shaded(&self, ctx: &mut Context, points: &[Vec2]) {
-        let RGBA8 { r, g, b, a } = self.start_color;
+    pub fn draw_shaded(&self, canvas: &mut Canvas, points: &[Vec2]) {
+        let RGBA8 { r, g, b, .. } = self.start_color;
         let (sr, sg, sb) = (r, g, b);
-        let RGBA8 { r, g, b, a } = self.end_color;
+        let RGBA8 { r, g, b, .. } = self.end_color;
         for point in points {
             let (x, y) = (point.x as i32, point.y as i32);
             let mix = self.line.todo_name(*point);
-            ctx.draw_pixel(
+            canvas.draw_pixel(
                 x,
                 y,
                 RGBA8::new(
@@ -167,7 +246,7 @@ impl Shading {
                     lerp_u8(sb, b, mix),
                     255,
                 ),
-            )
+            );
             //ctx.draw_pixel(x, y, dither(x, y, self.start_color, self.end_color, mix))
         }
         // {
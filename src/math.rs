@@ -1,6 +1,9 @@
-use std::ops::{Add, Mul, Sub};
+use std::cmp::Ordering;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use crate::geometry::{Line, Triangle};
+use crate::geometry::Triangle;
+use crate::predicates::orient2d;
 
 /// Get either width or height, depending on which is longer.
 pub fn diagonal_distance(from: Vec2, to: Vec2) -> f32 {
@@ -11,23 +14,22 @@ pub fn diagonal_distance(from: Vec2, to: Vec2) -> f32 {
 
 /*
     Determine if point is within triangle formed by points p1, p2, p3.
-    If so, the point will be on the same side of each of the half planes
-    defined by vectors p1p2, p2p3, and p3p1.
+    If so, the point will not be on opposite sides of any two of the lines
+    through p1p2, p2p3, and p3p1. Points on an edge count as inside,
+    triangles without area contain nothing.
 */
 pub fn point_is_in_triangle(point: Vec2, triangle: &Triangle) -> bool {
     let Triangle { a, b, c } = *triangle;
-    let side1 = side_of_the_plane(point, Line::new(a, b));
-    let side2 = side_of_the_plane(point, Line::new(b, c));
-    let side3 = side_of_the_plane(point, Line::new(c, a));
-    side1 && side2 && side3 || !side1 && !side2 && !side3
+    let sides = [
+        orient2d(a, b, point),
+        orient2d(b, c, point),
+        orient2d(c, a, point),
+    ];
+    orient2d(a, b, c).is_ne()
+        && !(sides.contains(&Ordering::Less) && sides.contains(&Ordering::Greater))
 }
 
-fn side_of_the_plane(point: Vec2, line: Line) -> bool {
-    let Line { a, b } = line;
-    ((point.x - b.x) * (a.y - b.y) - (a.x - b.x) * (point.y - b.y)).is_sign_positive()
-}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -59,24 +61,231 @@ impl Vec2 {
         self.dot(self)
     }
 
-    pub fn distance(self, other: Vec2) -> f32 {
+    pub fn distance(self, other: Self) -> f32 {
         (self - other).len()
     }
 
-    pub fn distance_squared(self, other: Vec2) -> f32 {
+    pub fn distance_squared(self, other: Self) -> f32 {
         (self - other).length_squared()
     }
 
-    pub fn dot(self, other: Vec2) -> f32 {
+    #[allow(clippy::suboptimal_flops)]
+    pub fn dot(self, other: Self) -> f32 {
         (self.x * other.x) + (self.y * other.y)
     }
 
+    /// Z component of the 3d cross product, positive when `other` is counter-clockwise from `self` (y up).
+    // Unfused so that `v.cross(v)` is exactly zero, parallel checks compare against 0.0.
+    #[allow(clippy::suboptimal_flops)]
+    pub fn cross(self, other: Self) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    #[allow(clippy::suboptimal_flops)]
     pub fn rotate(self, angle: f32) -> Self {
         Self {
             x: self.x * angle.cos() - self.y * angle.sin(),
             y: self.x * angle.sin() + self.y * angle.cos(),
         }
     }
+
+    /// Unit vector in the same direction, zero stays zero.
+    pub fn normalize(self) -> Self {
+        let len = self.len();
+        if len > 0.0 {
+            self / len
+        } else {
+            self
+        }
+    }
+
+    /// Rotated by 90 degrees, same rotation direction as a positive `rotate`.
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Angle from the positive x axis, in (-PI, PI].
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Signed angle that rotates `self` onto `other`.
+    pub fn angle_between(self, other: Self) -> f32 {
+        self.cross(other).atan2(self.dot(other))
+    }
+
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Mirrors the vector off a surface with the given unit `normal`.
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+
+    /// Scales the vector so its length is within [min, max], zero stays zero.
+    pub fn clamp_length(self, min: f32, max: f32) -> Self {
+        let len = self.len();
+        if len > max {
+            self * (max / len)
+        } else if len < min && len > 0.0 {
+            self * (min / len)
+        } else {
+            self
+        }
+    }
+
+    pub fn abs(self) -> Self {
+        Self::new(self.x.abs(), self.y.abs())
+    }
+
+    /// Component-wise minimum.
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// Component-wise maximum.
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn floor(self) -> Self {
+        Self::new(self.x.floor(), self.y.floor())
+    }
+
+    pub fn ceil(self) -> Self {
+        Self::new(self.x.ceil(), self.y.ceil())
+    }
+}
+
+/// 2d affine transform, maps a point `p` to `x_axis * p.x + y_axis * p.y + translation`.
+/// `a * b` applies `b` first, then `a`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Affine2 {
+    pub x_axis: Vec2,
+    pub y_axis: Vec2,
+    pub translation: Vec2,
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self {
+        x_axis: Vec2 { x: 1.0, y: 0.0 },
+        y_axis: Vec2 { x: 0.0, y: 1.0 },
+        translation: Vec2 { x: 0.0, y: 0.0 },
+    };
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotates in the same direction as `Vec2::rotate`.
+    pub fn from_rotation(angle: f32) -> Self {
+        Self::from_scale_rotation_translation(Vec2::new(1.0, 1.0), angle, Vec2::default())
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self {
+            x_axis: Vec2::new(scale.x, 0.0),
+            y_axis: Vec2::new(0.0, scale.y),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Scales, then rotates, then translates.
+    pub fn from_scale_rotation_translation(scale: Vec2, angle: f32, translation: Vec2) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            x_axis: Vec2::new(cos, sin) * scale.x,
+            y_axis: Vec2::new(-sin, cos) * scale.y,
+            translation,
+        }
+    }
+
+    /// Rotation that keeps `pivot` in place.
+    pub fn from_rotation_around(pivot: Vec2, angle: f32) -> Self {
+        Self::from_translation(pivot) * Self::from_rotation(angle) * Self::from_translation(-pivot)
+    }
+
+    /// Scaling that keeps `pivot` in place.
+    pub fn from_scale_around(pivot: Vec2, scale: Vec2) -> Self {
+        Self::from_translation(pivot) * Self::from_scale(scale) * Self::from_translation(-pivot)
+    }
+
+    pub fn determinant(self) -> f32 {
+        self.x_axis.cross(self.y_axis)
+    }
+
+    /// `None` when the transform collapses the plane onto a line or a point.
+    pub fn inverse(self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let inverse_det = determinant.recip();
+        let x_axis = Vec2::new(self.y_axis.y, -self.x_axis.y) * inverse_det;
+        let y_axis = Vec2::new(-self.y_axis.x, self.x_axis.x) * inverse_det;
+        let linear = Self {
+            x_axis,
+            y_axis,
+            translation: Vec2::default(),
+        };
+        Some(Self {
+            translation: -linear.transform_vector(self.translation),
+            ..linear
+        })
+    }
+
+    pub fn transform_point(self, point: Vec2) -> Vec2 {
+        self.transform_vector(point) + self.translation
+    }
+
+    /// Ignores the translation, for directions and offsets.
+    pub fn transform_vector(self, vector: Vec2) -> Vec2 {
+        self.x_axis * vector.x + self.y_axis * vector.y
+    }
+
+    /// Inverse of `from_scale_rotation_translation`, meaningless if the transform has shear.
+    pub fn to_scale_rotation_translation(self) -> (Vec2, f32, Vec2) {
+        let scale = Vec2::new(
+            self.x_axis.len() * self.determinant().signum(),
+            self.y_axis.len(),
+        );
+        let angle = (-self.y_axis.x).atan2(self.y_axis.y);
+        (scale, angle, self.translation)
+    }
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul<Self> for Affine2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            x_axis: self.transform_vector(rhs.x_axis),
+            y_axis: self.transform_vector(rhs.y_axis),
+            translation: self.transform_point(rhs.translation),
+        }
+    }
+}
+
+impl MulAssign<Self> for Affine2 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self::new(x, y)
+    }
 }
 
 impl Mul<f32> for Vec2 {
@@ -90,13 +299,55 @@ impl Mul<f32> for Vec2 {
     }
 }
 
+impl Mul<Vec2> for f32 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: Vec2) -> Self::Output {
+        rhs * self
+    }
+}
+
+/// Component-wise product.
+impl Mul<Self> for Vec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x * rhs.x,
+            y: self.y * rhs.y,
+        }
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self::Output {
+        Self {
+            x: self.x / rhs,
+            y: self.y / rhs,
+        }
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
 impl Sub<&Self> for Vec2 {
     type Output = Self;
 
     fn sub(self, rhs: &Self) -> Self::Output {
         Self {
-            x: rhs.x - self.x,
-            y: rhs.y - self.y,
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
         }
     }
 }
@@ -106,8 +357,8 @@ impl Sub<Self> for Vec2 {
 
     fn sub(self, rhs: Self) -> Self::Output {
         Self {
-            x: rhs.x - self.x,
-            y: rhs.y - self.y,
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
         }
     }
 }
@@ -133,3 +384,170 @@ impl Add<Self> for Vec2 {
         }
     }
 }
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
+impl MulAssign<Self> for Vec2 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign<f32> for Vec2 {
+    fn div_assign(&mut self, rhs: f32) {
+        *self = *self / rhs;
+    }
+}
+
+impl Sum for Vec2 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Self> for Vec2 {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-5
+    }
+
+    #[test]
+    fn operators() {
+        let (a, b) = (Vec2::new(3.0, -1.0), Vec2::new(1.0, 2.0));
+        assert_eq!(a - b, Vec2::new(2.0, -3.0));
+        assert_eq!(a + b, Vec2::new(4.0, 1.0));
+        assert_eq!(a * b, Vec2::new(3.0, -2.0));
+        assert_eq!(a * 2.0, Vec2::new(6.0, -2.0));
+        assert_eq!(a / 2.0, Vec2::new(1.5, -0.5));
+        assert_eq!(-a, Vec2::new(-3.0, 1.0));
+        let mut c = a;
+        c += b;
+        c -= b;
+        c *= 2.0;
+        c /= 2.0;
+        c *= Vec2::new(1.0, -1.0);
+        assert_eq!(c, Vec2::new(3.0, 1.0));
+        assert_eq!([a, b].into_iter().sum::<Vec2>(), Vec2::new(4.0, 1.0));
+        assert_eq!([a, b].iter().sum::<Vec2>(), Vec2::new(4.0, 1.0));
+    }
+
+    #[test]
+    fn cross_of_parallel_vectors_is_exactly_zero() {
+        let a = Vec2::new(1.1, 2.7);
+        assert_eq!(a.cross(a), 0.0);
+        assert_eq!(a.cross(a * 2.0), 0.0);
+        assert_eq!(a.cross(-a), 0.0);
+        let b = Vec2::new(-0.3, 5.9);
+        assert_eq!(a.cross(b), -b.cross(a));
+        assert!(Vec2::new(1.0, 0.0).cross(Vec2::new(0.0, 1.0)) > 0.0);
+    }
+
+    #[test]
+    fn directions() {
+        let a = Vec2::new(3.0, 4.0);
+        assert_eq!(a.len(), 5.0);
+        assert!(close(a.normalize(), Vec2::new(0.6, 0.8)));
+        assert_eq!(Vec2::default().normalize(), Vec2::default());
+        assert!(close(a.perp(), a.rotate(FRAC_PI_2)));
+        assert!((Vec2::new(1.0, 0.0).angle_between(Vec2::new(0.0, -1.0)) + FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(
+            Vec2::new(1.0, -1.0).reflect(Vec2::new(0.0, 1.0)),
+            Vec2::new(1.0, 1.0)
+        );
+        assert!((a.clamp_length(0.0, 2.0).len() - 2.0).abs() < 1e-6);
+        assert!((a.clamp_length(10.0, 20.0).len() - 10.0).abs() < 1e-5);
+        assert_eq!(Vec2::default().clamp_length(1.0, 2.0), Vec2::default());
+        assert_eq!(a.lerp(Vec2::default(), 0.5), Vec2::new(1.5, 2.0));
+    }
+
+    #[test]
+    fn affine_applies_the_right_hand_side_first() {
+        let scale = Affine2::from_scale(Vec2::new(2.0, 3.0));
+        let shift = Affine2::from_translation(Vec2::new(1.0, 0.0));
+        let point = Vec2::new(1.0, 1.0);
+        assert_eq!((shift * scale).transform_point(point), Vec2::new(3.0, 3.0));
+        assert_eq!((scale * shift).transform_point(point), Vec2::new(4.0, 3.0));
+        assert_eq!(shift.transform_vector(point), point);
+        let mut combined = shift;
+        combined *= scale;
+        assert_eq!(combined, shift * scale);
+    }
+
+    #[test]
+    fn rotation_matches_vec2_rotate_and_keeps_the_pivot() {
+        let point = Vec2::new(2.0, 1.0);
+        let rotation = Affine2::from_rotation(0.7);
+        assert!(close(rotation.transform_point(point), point.rotate(0.7)));
+        let pivot = Vec2::new(5.0, -3.0);
+        let around = Affine2::from_rotation_around(pivot, 1.2);
+        assert!(close(around.transform_point(pivot), pivot));
+        let scaled = Affine2::from_scale_around(pivot, Vec2::new(2.0, 2.0));
+        assert!(close(scaled.transform_point(pivot), pivot));
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = Affine2::from_scale_rotation_translation(
+            Vec2::new(2.0, 0.5),
+            0.3,
+            Vec2::new(4.0, -1.0),
+        );
+        let inverse = transform.inverse().unwrap();
+        for point in [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, -7.0),
+            Vec2::new(-1.5, 2.5),
+        ] {
+            assert!(close(
+                inverse.transform_point(transform.transform_point(point)),
+                point
+            ));
+        }
+        // Parallel axes give a determinant of exactly zero.
+        let axis = Vec2::new(1.1, 2.7);
+        let flat = Affine2 {
+            x_axis: axis,
+            y_axis: axis * 2.0,
+            translation: Vec2::default(),
+        };
+        assert_eq!(flat.inverse(), None);
+        assert_eq!(Affine2::from_scale(Vec2::new(0.0, 1.0)).inverse(), None);
+    }
+
+    #[test]
+    fn decomposes_what_it_composed() {
+        let (scale, angle, translation) = (Vec2::new(2.0, 3.0), -0.8, Vec2::new(1.0, 2.0));
+        let transform = Affine2::from_scale_rotation_translation(scale, angle, translation);
+        let (s, a, t) = transform.to_scale_rotation_translation();
+        assert!(close(s, scale));
+        assert!((a - angle).abs() < 1e-6);
+        assert_eq!(t, translation);
+    }
+}
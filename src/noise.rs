@@ -0,0 +1,896 @@
+//! Seedable gradient noise, the same seed always produces the same values.
+
+#![allow(clippy::many_single_char_names)]
+
+use std::f32::consts::TAU;
+
+use fastrand::Rng;
+
+use crate::math::Vec2;
+
+/// Gradient noise sampled in 2, 3 or 4 dimensions, results are roughly in [-1, 1].
+pub trait Noise {
+    fn noise2(&self, x: f32, y: f32) -> f32;
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32;
+    fn noise4(&self, x: f32, y: f32, z: f32, w: f32) -> f32;
+}
+
+/// Shuffled lattice hash shared by the noise types.
+#[derive(Clone)]
+struct Permutation([u8; 512]);
+
+impl Permutation {
+    fn new(seed: u64) -> Self {
+        let mut values: Vec<u8> = (0..=255).collect();
+        Rng::with_seed(seed).shuffle(&mut values);
+        let mut table = [0; 512];
+        for (i, entry) in table.iter_mut().enumerate() {
+            *entry = values[i & 255];
+        }
+        Self(table)
+    }
+
+    fn hash(&self, coordinates: &[i32]) -> u8 {
+        coordinates.iter().fold(0, |hash, coordinate| {
+            self.0[usize::from(hash) + (coordinate & 255) as usize]
+        })
+    }
+}
+
+fn grad2(hash: u8, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// One of the 12 cube edge directions (the 4 extra entries repeat some of them).
+fn grad3(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+/// One of the 32 directions from the center of a tesseract to the middle of its edges.
+fn grad4(hash: u8, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let h = hash & 31;
+    let (a, b, c) = match h >> 3 {
+        0 => (y, z, w),
+        1 => (x, z, w),
+        2 => (x, y, w),
+        _ => (x, y, z),
+    };
+    (if h & 4 == 0 { -a } else { a })
+        + (if h & 2 == 0 { -b } else { b })
+        + (if h & 1 == 0 { -c } else { c })
+}
+
+#[allow(clippy::suboptimal_flops)]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[allow(clippy::suboptimal_flops)]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Lattice cell of `value` and the offset inside of it.
+fn cell(value: f32) -> (i32, f32) {
+    let floor = value.floor();
+    (floor as i32, value - floor)
+}
+
+/// Wraps a lattice coordinate into [0, period).
+fn wrap(value: i32, period: i32) -> i32 {
+    value.rem_euclid(period)
+}
+
+/// Ken Perlin's improved noise.
+#[derive(Clone)]
+pub struct Perlin {
+    permutation: Permutation,
+}
+
+impl Perlin {
+    /// The lattice repeats after this many units unless a smaller period is asked for.
+    const PERIOD: i32 = 256;
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+
+    /// Noise that repeats every `period_x` units along x and `period_y` along y, for seamless textures.
+    /// Panics if a period is 0.
+    pub fn periodic2(&self, x: f32, y: f32, period_x: u32, period_y: u32) -> f32 {
+        assert!(
+            period_x > 0 && period_y > 0,
+            "noise periods should be positive"
+        );
+        self.sample2(x, y, [period_x as i32, period_y as i32])
+    }
+
+    /// Panics if a period is 0.
+    pub fn periodic3(&self, x: f32, y: f32, z: f32, period: [u32; 3]) -> f32 {
+        assert!(
+            period.iter().all(|period| *period > 0),
+            "noise periods should be positive"
+        );
+        self.sample3(x, y, z, period.map(|period| period as i32))
+    }
+
+    fn sample2(&self, x: f32, y: f32, period: [i32; 2]) -> f32 {
+        let (xi, xf) = cell(x);
+        let (yi, yf) = cell(y);
+        let (x0, x1) = (wrap(xi, period[0]), wrap(xi + 1, period[0]));
+        let (y0, y1) = (wrap(yi, period[1]), wrap(yi + 1, period[1]));
+        let hash = |x, y| self.permutation.hash(&[x, y]);
+
+        let (u, v) = (fade(xf), fade(yf));
+        let bottom = lerp(
+            grad2(hash(x0, y0), xf, yf),
+            grad2(hash(x1, y0), xf - 1.0, yf),
+            u,
+        );
+        let top = lerp(
+            grad2(hash(x0, y1), xf, yf - 1.0),
+            grad2(hash(x1, y1), xf - 1.0, yf - 1.0),
+            u,
+        );
+        lerp(bottom, top, v)
+    }
+
+    fn sample3(&self, x: f32, y: f32, z: f32, period: [i32; 3]) -> f32 {
+        let (xi, xf) = cell(x);
+        let (yi, yf) = cell(y);
+        let (zi, zf) = cell(z);
+        let xs = [wrap(xi, period[0]), wrap(xi + 1, period[0])];
+        let ys = [wrap(yi, period[1]), wrap(yi + 1, period[1])];
+        let zs = [wrap(zi, period[2]), wrap(zi + 1, period[2])];
+        let corner = |dx: usize, dy: usize, dz: usize| {
+            let hash = self.permutation.hash(&[xs[dx], ys[dy], zs[dz]]);
+            grad3(hash, xf - dx as f32, yf - dy as f32, zf - dz as f32)
+        };
+
+        let (u, v, w) = (fade(xf), fade(yf), fade(zf));
+        let near = lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        );
+        let far = lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        );
+        lerp(near, far, w)
+    }
+}
+
+impl Noise for Perlin {
+    fn noise2(&self, x: f32, y: f32) -> f32 {
+        self.sample2(x, y, [Self::PERIOD; 2])
+    }
+
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.sample3(x, y, z, [Self::PERIOD; 3])
+    }
+
+    fn noise4(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        let coordinates = [x, y, z, w].map(cell);
+        let offsets = coordinates.map(|(_, offset)| offset);
+        let faded = offsets.map(fade);
+
+        // Interpolate the 16 corners of the hypercube, one axis at a time.
+        let mut corners = [0.0; 16];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let step = |axis: usize| (i >> axis) & 1;
+            let lattice: [i32; 4] =
+                std::array::from_fn(|axis| coordinates[axis].0 + step(axis) as i32);
+            let d: [f32; 4] = std::array::from_fn(|axis| offsets[axis] - step(axis) as f32);
+            *corner = grad4(self.permutation.hash(&lattice), d[0], d[1], d[2], d[3]);
+        }
+        let mut len = corners.len();
+        for t in faded {
+            len /= 2;
+            for i in 0..len {
+                corners[i] = lerp(corners[i * 2], corners[i * 2 + 1], t);
+            }
+        }
+        corners[0]
+    }
+}
+
+/// `OpenSimplex2` noise, it has fewer directional artifacts than `Perlin` and is cheaper in
+/// higher dimensions. In 2d it uses the triangular lattice of simplex noise, in 3d two
+/// interleaved cubic lattices rotated to stand on their diagonal, and in 4d five offset
+/// copies of the 4d simplex lattice.
+#[derive(Clone)]
+pub struct OpenSimplex {
+    permutation: Permutation,
+}
+
+impl OpenSimplex {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+}
+
+/// Radial falloff of a lattice point, zero outside of its radius.
+fn kernel(radius_squared: f32, distance_squared: f32) -> f32 {
+    let t = radius_squared - distance_squared;
+    if t > 0.0 {
+        t * t * t * t
+    } else {
+        0.0
+    }
+}
+
+impl Noise for OpenSimplex {
+    fn noise2(&self, x: f32, y: f32) -> f32 {
+        let skew = 0.5 * (3.0_f32.sqrt() - 1.0);
+        let unskew = (3.0 - 3.0_f32.sqrt()) / 6.0;
+
+        let s = (x + y) * skew;
+        let (xi, xf) = cell(x + s);
+        let (yi, yf) = cell(y + s);
+        let t = (xf + yf) * unskew;
+        let (dx, dy) = (xf - t, yf - t);
+        // The cell is split along its diagonal, the third corner is on the side of the point.
+        let (step_x, step_y) = if dx > dy { (1, 0) } else { (0, 1) };
+        let corners = [
+            (0, 0, dx, dy),
+            (
+                step_x,
+                step_y,
+                dx - step_x as f32 + unskew,
+                dy - step_y as f32 + unskew,
+            ),
+            (
+                1,
+                1,
+                unskew.mul_add(2.0, dx - 1.0),
+                unskew.mul_add(2.0, dy - 1.0),
+            ),
+        ];
+        let sum: f32 = corners
+            .into_iter()
+            .map(|(step_x, step_y, dx, dy)| {
+                let hash = self.permutation.hash(&[xi + step_x, yi + step_y]);
+                kernel(0.5, dx.mul_add(dx, dy * dy)) * grad2(hash, dx, dy)
+            })
+            .sum();
+        sum * 70.0
+    }
+
+    fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        // Turning the lattice so its main diagonal runs along (1, 1, 1) hides the cube axes.
+        let r = (x + y + z) * (2.0 / 3.0);
+        let position = [r - x, r - y, r - z];
+        let mut lattice = position.map(|value| value.round() as i32);
+        let mut d: [f32; 3] = std::array::from_fn(|axis| position[axis] - lattice[axis] as f32);
+        // Stepping by `-sign` along an axis moves to the lattice point on the side of the sample.
+        let mut sign = d.map(|value| if value >= 0.0 { -1.0 } else { 1.0 });
+        let mut away = d.map(f32::abs);
+        // Within this radius at most two points of each lattice reach the sample, the nearest
+        // one and a step from it, so visiting those keeps the noise continuous.
+        let mut falloff = 0.5 - d.iter().map(|value| value * value).sum::<f32>();
+
+        let mut sum = 0.0;
+        for copy in 0..2 {
+            let gradient = |lattice: [i32; 3], d: [f32; 3]| {
+                let hash = self
+                    .permutation
+                    .hash(&[lattice[0], lattice[1], lattice[2], copy]);
+                grad3(hash, d[0], d[1], d[2])
+            };
+            if falloff > 0.0 {
+                sum += falloff.powi(4) * gradient(lattice, d);
+            }
+            // The second nearest point of this lattice is a step along the axis the point
+            // is furthest off on.
+            let axis = if away[0] >= away[1] && away[0] >= away[2] {
+                0
+            } else if away[1] > away[0] && away[1] >= away[2] {
+                1
+            } else {
+                2
+            };
+            let second = away[axis].mul_add(2.0, falloff) - 1.0;
+            if second > 0.0 {
+                let mut neighbour = lattice;
+                neighbour[axis] -= sign[axis] as i32;
+                let mut offset = d;
+                offset[axis] += sign[axis];
+                sum += second.powi(4) * gradient(neighbour, offset);
+            }
+            if copy == 1 {
+                break;
+            }
+            // The other lattice is shifted by half a cell along every axis.
+            falloff += away.iter().sum::<f32>() - 0.75;
+            for axis in 0..3 {
+                away[axis] = 0.5 - away[axis];
+                d[axis] = sign[axis] * away[axis];
+                if sign[axis] < 0.0 {
+                    lattice[axis] += 1;
+                }
+                sign[axis] = -sign[axis];
+            }
+        }
+        sum * 76.0
+    }
+
+    fn noise4(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        let skew = (1.0 / 5.0_f32.sqrt() - 1.0) / 4.0;
+        let unskew = (5.0_f32.sqrt() - 1.0) / 4.0;
+        let step = 0.2;
+
+        let s = (x + y + z + w) * skew;
+        let cells = [x, y, z, w].map(|value| cell(value + s));
+        let mut lattice = cells.map(|(index, _)| index);
+        let mut offsets = cells.map(|(_, offset)| offset);
+        // Start on the lattice copy the point is in, the others are `step` further along the diagonal.
+        let offset_sum: f32 = offsets.iter().sum();
+        let start = (offset_sum * 1.25) as i32;
+        let shift = start as f32 * -step;
+        for offset in &mut offsets {
+            *offset += shift;
+        }
+        let mut diagonal = shift.mul_add(4.0, offset_sum) * unskew;
+
+        let mut sum = 0.0;
+        for copy in 0..5 {
+            // The nearest vertex of the simplex the point is in on this copy, the only one
+            // that can reach it within the radius.
+            let score = 1.0 - diagonal / unskew;
+            let largest = (0..4).find(|&axis| {
+                offsets[axis] >= score
+                    && (0..4).all(|other| {
+                        other == axis
+                            || offsets[axis] > offsets[other]
+                            || (other > axis && offsets[axis] >= offsets[other])
+                    })
+            });
+            if let Some(axis) = largest {
+                lattice[axis] += 1;
+                offsets[axis] -= 1.0;
+                diagonal -= unskew;
+            }
+            let d = offsets.map(|offset| offset + diagonal);
+            let hash = self.permutation.hash(&[
+                lattice[0],
+                lattice[1],
+                lattice[2],
+                lattice[3],
+                (start - copy).rem_euclid(5),
+            ]);
+            let distance_squared = d.iter().map(|value| value * value).sum();
+            sum += kernel(0.5, distance_squared) * grad4(hash, d[0], d[1], d[2], d[3]);
+            if copy == 4 {
+                break;
+            }
+            for offset in &mut offsets {
+                *offset += step;
+            }
+            diagonal += step * 4.0 * unskew;
+            if copy == start {
+                lattice = lattice.map(|index| index - 1);
+            }
+        }
+        sum * 62.0
+    }
+}
+
+/// Seamless 2d noise over a `width` x `height` tile, made by walking around a torus in 4d noise.
+/// `frequency` is the number of features across the tile.
+pub fn tileable2<N: Noise>(
+    noise: &N,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    frequency: f32,
+) -> f32 {
+    let radius = frequency / TAU;
+    let (sin_x, cos_x) = (x / width * TAU).sin_cos();
+    let (sin_y, cos_y) = (y / height * TAU).sin_cos();
+    noise.noise4(
+        cos_x * radius,
+        sin_x * radius,
+        cos_y * radius,
+        sin_y * radius,
+    )
+}
+
+/// Sums octaves of noise, each one `lacunarity` times finer and `gain` times weaker than the last.
+#[derive(Debug, Copy, Clone)]
+pub struct Fractal {
+    pub octaves: u32,
+    pub frequency: f32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self {
+            octaves: 4,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+#[allow(clippy::suboptimal_flops)]
+impl Fractal {
+    pub fn new(octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        Self {
+            octaves,
+            lacunarity,
+            gain,
+            ..Self::default()
+        }
+    }
+
+    /// Fractal Brownian motion, in [-1, 1].
+    pub fn fbm2<N: Noise>(&self, noise: &N, x: f32, y: f32) -> f32 {
+        self.accumulate(|f, octave| noise.noise2(x * f + octave, y * f), |n| n)
+    }
+
+    pub fn fbm3<N: Noise>(&self, noise: &N, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(
+            |f, octave| noise.noise3(x * f + octave, y * f, z * f),
+            |n| n,
+        )
+    }
+
+    /// Sharp ridges where the noise crosses zero, in [0, 1].
+    pub fn ridged2<N: Noise>(&self, noise: &N, x: f32, y: f32) -> f32 {
+        self.accumulate(|f, octave| noise.noise2(x * f + octave, y * f), ridge)
+    }
+
+    pub fn ridged3<N: Noise>(&self, noise: &N, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(
+            |f, octave| noise.noise3(x * f + octave, y * f, z * f),
+            ridge,
+        )
+    }
+
+    /// Sum of absolute values, billowy creases, in [0, 1].
+    pub fn turbulence2<N: Noise>(&self, noise: &N, x: f32, y: f32) -> f32 {
+        self.accumulate(|f, octave| noise.noise2(x * f + octave, y * f), f32::abs)
+    }
+
+    pub fn turbulence3<N: Noise>(&self, noise: &N, x: f32, y: f32, z: f32) -> f32 {
+        self.accumulate(
+            |f, octave| noise.noise3(x * f + octave, y * f, z * f),
+            f32::abs,
+        )
+    }
+
+    /// `sample` gets the octave frequency and an offset that keeps the octaves from lining up at the origin.
+    fn accumulate(&self, sample: impl Fn(f32, f32) -> f32, shape: impl Fn(f32) -> f32) -> f32 {
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+        for octave in 0..self.octaves {
+            let value = sample(frequency, octave as f32 * 17.13).clamp(-1.0, 1.0);
+            sum += shape(value) * amplitude;
+            total_amplitude += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        if total_amplitude > 0.0 {
+            sum / total_amplitude
+        } else {
+            0.0
+        }
+    }
+}
+
+fn ridge(value: f32) -> f32 {
+    let ridge = 1.0 - value.abs();
+    ridge * ridge
+}
+
+/// Pushes `point` around by fbm, sample anything at the result for swirly, marbled patterns.
+pub fn warp2<N: Noise>(noise: &N, fractal: &Fractal, point: Vec2, strength: f32) -> Vec2 {
+    // Offsets decorrelate the x and y displacement.
+    let offset = Vec2::new(
+        fractal.fbm2(noise, point.x, point.y),
+        fractal.fbm2(noise, point.x + 5.2, point.y + 1.3),
+    );
+    point + offset * strength
+}
+
+/// fbm sampled at a warped position, `f(p + strength * fbm(p))`.
+pub fn warped_fbm2<N: Noise>(noise: &N, fractal: &Fractal, point: Vec2, strength: f32) -> f32 {
+    let warped = warp2(noise, fractal, point, strength);
+    fractal.fbm2(noise, warped.x, warped.y)
+}
+
+/// How distances to the feature points are measured, changes the shape of the cells.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Metric {
+    /// Round cells.
+    Euclidean,
+    /// Diamond shaped cells.
+    Manhattan,
+    /// Square cells.
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(self, offset: &[f32]) -> f32 {
+        match self {
+            Self::Euclidean => offset.iter().map(|d| d * d).sum::<f32>().sqrt(),
+            Self::Manhattan => offset.iter().map(|d| d.abs()).sum(),
+            Self::Chebyshev => offset.iter().fold(0.0, |max, d| d.abs().max(max)),
+        }
+    }
+}
+
+/// Result of sampling cellular noise.
+#[derive(Debug, Copy, Clone)]
+pub struct CellSample {
+    /// Distance to the closest feature point.
+    pub f1: f32,
+    /// Distance to the second closest feature point.
+    pub f2: f32,
+    /// Stable id of the closest cell, use it to pick a color from a palette.
+    pub cell_id: u32,
+}
+
+impl CellSample {
+    /// `f2 - f1`, zero on the borders between cells, good for cracks.
+    pub fn edge(&self) -> f32 {
+        self.f2 - self.f1
+    }
+}
+
+/// Worley (cellular) noise, one feature point per lattice cell.
+#[derive(Debug, Copy, Clone)]
+pub struct Worley {
+    seed: u32,
+    pub metric: Metric,
+    /// 0 puts the feature points on a regular grid, 1 scatters them across the whole cell.
+    pub jitter: f32,
+}
+
+impl Worley {
+    pub fn new(seed: u64, metric: Metric, jitter: f32) -> Self {
+        Self {
+            seed: (seed ^ (seed >> 32)) as u32,
+            metric,
+            jitter: jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn sample2(&self, x: f32, y: f32) -> CellSample {
+        self.sample([x, y])
+    }
+
+    pub fn sample3(&self, x: f32, y: f32, z: f32) -> CellSample {
+        self.sample([x, y, z])
+    }
+
+    /// Searches rings of cells around the one `point` is in until no cell further out can
+    /// hold a point closer than F2. With full jitter or the Manhattan metric the second
+    /// closest point can be two cells away, past the usual 3x3 block.
+    fn sample<const N: usize>(&self, point: [f32; N]) -> CellSample {
+        let origin = point.map(|value| value.floor() as i32);
+        // Shortest way out of the origin cell, a cell `ring + 1` steps away is at least
+        // `ring + margin` away along one axis, whatever the metric.
+        let margin = point
+            .iter()
+            .zip(origin)
+            .map(|(value, cell)| {
+                let inside = value - cell as f32;
+                inside.min(1.0 - inside)
+            })
+            .fold(f32::MAX, f32::min);
+        let mut nearest = Nearest::new();
+        let mut ring = 0;
+        loop {
+            for step in ring_steps::<N>(ring) {
+                let cell: [i32; N] = std::array::from_fn(|axis| origin[axis] + step[axis]);
+                let hash = hash_cell(self.seed, &cell);
+                let offset: [f32; N] = std::array::from_fn(|axis| {
+                    cell[axis] as f32 + self.feature_offset(hash >> (8 * axis)) - point[axis]
+                });
+                nearest.insert(self.metric.distance(&offset), hash_cell(hash, &cell));
+            }
+            if nearest.f2 <= ring as f32 + margin {
+                break;
+            }
+            ring += 1;
+        }
+        nearest.sample()
+    }
+
+    /// Position of the feature point along one axis of its cell, from 8 bits of the cell hash.
+    #[allow(clippy::suboptimal_flops)]
+    fn feature_offset(&self, hash: u32) -> f32 {
+        let random = f32::from((hash & 255) as u8) / 255.0;
+        0.5 + (random - 0.5) * self.jitter
+    }
+}
+
+/// Keeps the two smallest distances seen so far.
+struct Nearest {
+    f1: f32,
+    f2: f32,
+    cell_id: u32,
+}
+
+impl Nearest {
+    fn new() -> Self {
+        Self {
+            f1: f32::MAX,
+            f2: f32::MAX,
+            cell_id: 0,
+        }
+    }
+
+    fn insert(&mut self, distance: f32, cell_id: u32) {
+        if distance < self.f1 {
+            self.f2 = self.f1;
+            self.f1 = distance;
+            self.cell_id = cell_id;
+        } else if distance < self.f2 {
+            self.f2 = distance;
+        }
+    }
+
+    fn sample(&self) -> CellSample {
+        CellSample {
+            f1: self.f1,
+            f2: self.f2,
+            cell_id: self.cell_id,
+        }
+    }
+}
+
+/// Offsets of the cells exactly `ring` steps from the center, counting diagonal steps as one.
+fn ring_steps<const N: usize>(ring: i32) -> impl Iterator<Item = [i32; N]> {
+    let side = 2 * ring + 1;
+    (0..side.pow(N as u32))
+        .map(move |index| std::array::from_fn(|axis| index / side.pow(axis as u32) % side - ring))
+        .filter(move |step: &[i32; N]| step.iter().any(|offset| offset.abs() == ring))
+}
+
+/// Mixes the seed and the lattice coordinates into 32 well distributed bits.
+fn hash_cell(seed: u32, coordinates: &[i32]) -> u32 {
+    let mut hash = seed;
+    for coordinate in coordinates {
+        hash ^= *coordinate as u32;
+        hash = hash.wrapping_mul(0x9E37_79B1);
+        hash ^= hash >> 16;
+        hash = hash.wrapping_mul(0x85EB_CA6B);
+        hash ^= hash >> 13;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points spread over a few lattice cells, off the lattice itself.
+    fn samples() -> impl Iterator<Item = [f32; 4]> {
+        (0..2000).map(|i| {
+            let i = i as f32;
+            [
+                i.mul_add(0.137, -50.0),
+                i.mul_add(0.071, 3.3),
+                i.mul_add(0.053, -7.1),
+                i.mul_add(0.029, 0.4),
+            ]
+        })
+    }
+
+    fn check_range_and_continuity<N: Noise>(noise: &N) {
+        let step = 1e-3;
+        let mut largest: f32 = 0.0;
+        for [x, y, z, w] in samples() {
+            for (value, nudged) in [
+                (noise.noise2(x, y), noise.noise2(x + step, y)),
+                (noise.noise3(x, y, z), noise.noise3(x, y + step, z)),
+                (noise.noise4(x, y, z, w), noise.noise4(x, y, z, w + step)),
+            ] {
+                assert!(value.abs() <= 1.0, "{value} at {x} {y} {z} {w}");
+                assert!((value - nudged).abs() < 0.02, "jump at {x} {y} {z} {w}");
+                largest = largest.max(value.abs());
+            }
+        }
+        assert!(largest > 0.5, "{largest}");
+    }
+
+    #[test]
+    fn perlin_stays_in_range_and_is_continuous() {
+        check_range_and_continuity(&Perlin::new(1));
+    }
+
+    #[test]
+    fn open_simplex_stays_in_range_and_is_continuous() {
+        check_range_and_continuity(&OpenSimplex::new(1));
+    }
+
+    #[test]
+    fn seeds_are_reproducible() {
+        let (a, b, c) = (
+            OpenSimplex::new(4),
+            OpenSimplex::new(4),
+            OpenSimplex::new(5),
+        );
+        let values = |noise: &OpenSimplex| {
+            samples()
+                .take(50)
+                .map(|[x, y, z, _]| noise.noise3(x, y, z).to_bits())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(&a), values(&b));
+        assert_ne!(values(&a), values(&c));
+    }
+
+    #[test]
+    fn perlin_is_zero_on_the_lattice() {
+        let perlin = Perlin::new(9);
+        for (x, y) in [(0.0, 0.0), (3.0, -2.0), (17.0, 40.0)] {
+            assert!(perlin.noise2(x, y).abs() < 1e-6);
+            assert!(perlin.noise3(x, y, 5.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn periodic_noise_repeats() {
+        let perlin = Perlin::new(2);
+        for [x, y, z, _] in samples().take(200) {
+            let value = perlin.periodic2(x, y, 4, 3);
+            assert!((value - perlin.periodic2(x + 4.0, y - 3.0, 4, 3)).abs() < 1e-4);
+            let value = perlin.periodic3(x, y, z, [2, 5, 7]);
+            let shifted = perlin.periodic3(x - 2.0, y + 5.0, z + 14.0, [2, 5, 7]);
+            assert!((value - shifted).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "noise periods should be positive")]
+    fn zero_period_panics() {
+        Perlin::new(2).periodic2(0.5, 0.5, 0, 4);
+    }
+
+    #[test]
+    fn tileable_noise_wraps() {
+        let noise = OpenSimplex::new(3);
+        for y in [0.0, 13.0, 77.5] {
+            let left = tileable2(&noise, 0.0, y, 100.0, 80.0, 4.0);
+            let right = tileable2(&noise, 100.0, y, 100.0, 80.0, 4.0);
+            assert!((left - right).abs() < 1e-4);
+            let top = tileable2(&noise, y, 0.0, 100.0, 80.0, 4.0);
+            let bottom = tileable2(&noise, y, 80.0, 100.0, 80.0, 4.0);
+            assert!((top - bottom).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn fractal_sums_stay_in_their_ranges() {
+        let noise = Perlin::new(6);
+        let fractal = Fractal::default();
+        for [x, y, z, _] in samples().take(500) {
+            assert!(fractal.fbm2(&noise, x, y).abs() <= 1.0);
+            assert!(fractal.fbm3(&noise, x, y, z).abs() <= 1.0);
+            for value in [
+                fractal.ridged2(&noise, x, y),
+                fractal.ridged3(&noise, x, y, z),
+                fractal.turbulence2(&noise, x, y),
+                fractal.turbulence3(&noise, x, y, z),
+            ] {
+                assert!((0.0..=1.0).contains(&value), "{value}");
+            }
+        }
+        let silent = Fractal::new(0, 2.0, 0.5);
+        assert!(silent.fbm2(&noise, 0.3, 0.6).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn zero_strength_warp_stays_put() {
+        let (noise, fractal) = (Perlin::new(8), Fractal::default());
+        let point = Vec2::new(1.3, -2.4);
+        assert_eq!(warp2(&noise, &fractal, point, 0.0), point);
+        let warped = warp2(&noise, &fractal, point, 10.0);
+        // fbm is at most 1 on each axis.
+        let moved = (warped - point).abs();
+        assert!(moved.x <= 10.0 && moved.y <= 10.0 && moved != Vec2::default());
+    }
+
+    /// F1 and F2 from every cell within four steps, far more than any sample needs.
+    fn brute_force<const N: usize>(worley: &Worley, point: [f32; N]) -> (f32, f32) {
+        let mut distances: Vec<f32> = ring_steps::<N>(4)
+            .chain((0..4).flat_map(ring_steps::<N>))
+            .map(|step| {
+                let cell: [i32; N] =
+                    std::array::from_fn(|axis| point[axis].floor() as i32 + step[axis]);
+                let hash = hash_cell(worley.seed, &cell);
+                let offset: [f32; N] = std::array::from_fn(|axis| {
+                    cell[axis] as f32 + worley.feature_offset(hash >> (8 * axis)) - point[axis]
+                });
+                worley.metric.distance(&offset)
+            })
+            .collect();
+        distances.sort_by(f32::total_cmp);
+        (distances[0], distances[1])
+    }
+
+    #[test]
+    fn worley_finds_the_two_closest_points() {
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            for jitter in [0.3, 1.0] {
+                let worley = Worley::new(3, metric, jitter);
+                for [x, y, z, _] in samples().take(400) {
+                    let sample = worley.sample2(x, y);
+                    let expected = brute_force(&worley, [x, y]);
+                    assert_eq!((sample.f1, sample.f2), expected, "{metric:?} at {x} {y}");
+                    let sample = worley.sample3(x, y, z);
+                    let expected = brute_force(&worley, [x, y, z]);
+                    assert_eq!(
+                        (sample.f1, sample.f2),
+                        expected,
+                        "{metric:?} at {x} {y} {z}"
+                    );
+                    assert!(sample.edge() >= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn worley_without_jitter_is_a_grid() {
+        let worley = Worley::new(1, Metric::Euclidean, 0.0);
+        let center = worley.sample2(2.5, -3.5);
+        assert!(center.f1 < 1e-6 && (center.f2 - 1.0).abs() < 1e-6);
+        let border = worley.sample2(3.0, -3.5);
+        assert!(border.edge().abs() < 1e-6);
+    }
+
+    #[test]
+    fn worley_cell_ids_follow_the_closest_point() {
+        let worley = Worley::new(7, Metric::Euclidean, 0.0);
+        assert_eq!(
+            worley.sample2(2.4, 2.6).cell_id,
+            worley.sample2(2.6, 2.4).cell_id
+        );
+        assert_ne!(
+            worley.sample2(2.4, 2.6).cell_id,
+            worley.sample2(3.6, 2.4).cell_id
+        );
+    }
+
+    #[test]
+    fn metrics_measure_their_shapes() {
+        let offset = [3.0, -4.0];
+        assert!((Metric::Euclidean.distance(&offset) - 5.0).abs() < 1e-6);
+        assert!((Metric::Manhattan.distance(&offset) - 7.0).abs() < 1e-6);
+        assert!((Metric::Chebyshev.distance(&offset) - 4.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,441 @@
+//! Named, typed sketch parameters that can be tweaked while the sketch runs.
+
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use simple_pixels::rgb::RGBA8;
+
+use crate::math::Vec2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    Color(RGBA8),
+    Vec2(Vec2),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Color(RGBA8 { r, g, b, a: 255 }) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            Self::Color(RGBA8 { r, g, b, a }) => write!(f, "#{r:02x}{g:02x}{b:02x}{a:02x}"),
+            Self::Vec2(Vec2 { x, y }) => write!(f, "({x}, {y})"),
+        }
+    }
+}
+
+impl Value {
+    /// Parses `text` as the same kind of value as `self`.
+    pub fn parse_like(&self, text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        match self {
+            Self::Float(_) => text
+                .parse()
+                .map(Self::Float)
+                .map_err(|_| format!("expected a float, got {text:?}")),
+            Self::Int(_) => text
+                .parse()
+                .map(Self::Int)
+                .map_err(|_| format!("expected an int, got {text:?}")),
+            Self::Bool(_) => text
+                .parse()
+                .map(Self::Bool)
+                .map_err(|_| format!("expected true or false, got {text:?}")),
+            Self::Color(_) => parse_color(text)
+                .map(Self::Color)
+                .ok_or_else(|| format!("expected a #rrggbb or #rrggbbaa color, got {text:?}")),
+            Self::Vec2(_) => parse_vec2(text)
+                .map(Self::Vec2)
+                .ok_or_else(|| format!("expected a vector like (1.0, 2.0), got {text:?}")),
+        }
+    }
+
+    /// The value inside `[min, max]`, bools and colors are left alone.
+    fn clamped(self, min: f32, max: f32) -> Self {
+        match self {
+            Self::Float(value) => Self::Float(value.max(min).min(max)),
+            Self::Int(value) => Self::Int(value.max(min as i32).min(max as i32)),
+            Self::Vec2(Vec2 { x, y }) => {
+                Self::Vec2(Vec2::new(x.max(min).min(max), y.max(min).min(max)))
+            }
+            other => other,
+        }
+    }
+}
+
+fn parse_color(text: &str) -> Option<RGBA8> {
+    let hex = text.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Some(RGBA8::new(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+fn parse_vec2(text: &str) -> Option<Vec2> {
+    let text = text.trim_start_matches('(').trim_end_matches(')');
+    let (x, y) = text.split_once(',')?;
+    Some(Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub value: Value,
+    /// Range used by the gui sliders, ignored for bools and colors.
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct Params {
+    entries: Vec<Param>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_float(&mut self, name: &str, value: f32, min: f32, max: f32) -> &mut Self {
+        self.add(name, Value::Float(value), min, max)
+    }
+
+    pub fn add_int(&mut self, name: &str, value: i32, min: i32, max: i32) -> &mut Self {
+        self.add(name, Value::Int(value), min as f32, max as f32)
+    }
+
+    pub fn add_bool(&mut self, name: &str, value: bool) -> &mut Self {
+        self.add(name, Value::Bool(value), 0.0, 1.0)
+    }
+
+    pub fn add_color(&mut self, name: &str, value: RGBA8) -> &mut Self {
+        self.add(name, Value::Color(value), 0.0, 255.0)
+    }
+
+    pub fn add_vec2(&mut self, name: &str, value: Vec2, min: f32, max: f32) -> &mut Self {
+        self.add(name, Value::Vec2(value), min, max)
+    }
+
+    fn add(&mut self, name: &str, value: Value, min: f32, max: f32) -> &mut Self {
+        assert!(self.get(name).is_none(), "Parameter {name} added twice");
+        self.entries.push(Param {
+            name: name.to_string(),
+            value,
+            min,
+            max,
+        });
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Param> {
+        self.entries.iter().find(|param| param.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Param> {
+        self.entries.iter_mut().find(|param| param.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Param> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Param> {
+        self.entries.iter_mut()
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        match self.value(name) {
+            Value::Float(value) => value,
+            other => panic!("Parameter {name} is not a float: {other:?}"),
+        }
+    }
+
+    pub fn int(&self, name: &str) -> i32 {
+        match self.value(name) {
+            Value::Int(value) => value,
+            other => panic!("Parameter {name} is not an int: {other:?}"),
+        }
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        match self.value(name) {
+            Value::Bool(value) => value,
+            other => panic!("Parameter {name} is not a bool: {other:?}"),
+        }
+    }
+
+    pub fn color(&self, name: &str) -> RGBA8 {
+        match self.value(name) {
+            Value::Color(value) => value,
+            other => panic!("Parameter {name} is not a color: {other:?}"),
+        }
+    }
+
+    pub fn vec2(&self, name: &str) -> Vec2 {
+        match self.value(name) {
+            Value::Vec2(value) => value,
+            other => panic!("Parameter {name} is not a vec2: {other:?}"),
+        }
+    }
+
+    fn value(&self, name: &str) -> Value {
+        self.get(name)
+            .unwrap_or_else(|| panic!("No parameter named {name}"))
+            .value
+    }
+
+    /// Parses `text` according to the type of the parameter and stores it clamped to the
+    /// parameter's range, returns whether the value changed.
+    pub fn set_from_str(&mut self, name: &str, text: &str) -> Result<bool, String> {
+        let param = self
+            .get_mut(name)
+            .ok_or_else(|| format!("no parameter named {name:?}"))?;
+        let value = param.value.parse_like(text)?.clamped(param.min, param.max);
+        let changed = value != param.value;
+        param.value = value;
+        Ok(changed)
+    }
+
+    /// Applies every `name = value` line of `text`, blank lines and lines starting with `#` are skipped.
+    /// Returns the names of the changed parameters and a message for every line that couldn't be applied.
+    pub fn apply_text(&mut self, text: &str) -> (Vec<String>, Vec<String>) {
+        let mut changed = Vec::new();
+        let mut errors = Vec::new();
+        for (number, line) in text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+        {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                errors.push(format!(
+                    "line {number}: expected `name = value`, got {line:?}"
+                ));
+                continue;
+            };
+            let name = name.trim();
+            match self.set_from_str(name, value) {
+                Ok(true) => changed.push(name.to_string()),
+                Ok(false) => {}
+                Err(err) => errors.push(format!("line {number}: {err}")),
+            }
+        }
+        (changed, errors)
+    }
+
+    /// One `name = value` line per parameter.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for param in &self.entries {
+            let _ = writeln!(text, "{} = {}", param.name, param.value);
+        }
+        text
+    }
+
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        fs::write(path, self.to_text())
+    }
+}
+
+/// Watches a parameter file and applies it whenever its modification time changes.
+pub struct ParamsFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Option<Instant>,
+}
+
+impl ParamsFile {
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            modified: None,
+            last_poll: None,
+        }
+    }
+
+    /// Reloads the file if it changed since the last poll, problems are reported to the console.
+    pub fn poll(&mut self, params: &mut Params) {
+        if self
+            .last_poll
+            .is_some_and(|last_poll| last_poll.elapsed() < Self::POLL_INTERVAL)
+        {
+            return;
+        }
+        self.last_poll = Some(Instant::now());
+
+        // A missing file just means there is nothing to apply yet.
+        let Ok(modified) = fs::metadata(&self.path).and_then(|meta| meta.modified()) else {
+            return;
+        };
+        if self.modified == Some(modified) {
+            return;
+        }
+        self.modified = Some(modified);
+
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("couldn't read {}: {err}", self.path.display());
+                return;
+            }
+        };
+        let (changed, errors) = params.apply_text(&text);
+        for name in changed {
+            if let Some(param) = params.get(&name) {
+                println!("{name} = {}", param.value);
+            }
+        }
+        for error in errors {
+            eprintln!("{}: {error}", self.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_getters_read_what_was_added() {
+        let mut params = Params::new();
+        params
+            .add_float("speed", 0.5, 0.0, 1.0)
+            .add_int("count", 3, 0, 10)
+            .add_bool("trails", true)
+            .add_color("tint", RGBA8::new(1, 2, 3, 255))
+            .add_vec2("offset", Vec2::new(1.0, -1.0), -5.0, 5.0);
+        assert_eq!(params.float("speed").to_bits(), 0.5_f32.to_bits());
+        assert_eq!(params.int("count"), 3);
+        assert!(params.bool("trails"));
+        assert_eq!(params.color("tint"), RGBA8::new(1, 2, 3, 255));
+        assert_eq!(params.vec2("offset"), Vec2::new(1.0, -1.0));
+        let names: Vec<&str> = params.iter().map(|param| param.name.as_str()).collect();
+        assert_eq!(names, ["speed", "count", "trails", "tint", "offset"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "added twice")]
+    fn names_are_unique() {
+        Params::new()
+            .add_bool("trails", true)
+            .add_bool("trails", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a float")]
+    fn wrong_type_panics() {
+        let mut params = Params::new();
+        params.add_bool("trails", true);
+        params.float("trails");
+    }
+
+    #[test]
+    fn values_parse_like_their_kind() {
+        assert_eq!(Value::Float(0.0).parse_like(" 1.5 "), Ok(Value::Float(1.5)));
+        assert_eq!(Value::Int(0).parse_like("-4"), Ok(Value::Int(-4)));
+        assert_eq!(Value::Bool(false).parse_like("true"), Ok(Value::Bool(true)));
+        assert_eq!(
+            Value::Color(RGBA8::default()).parse_like("#0a0b0c"),
+            Ok(Value::Color(RGBA8::new(10, 11, 12, 255)))
+        );
+        assert_eq!(
+            Value::Color(RGBA8::default()).parse_like("#0a0b0c80"),
+            Ok(Value::Color(RGBA8::new(10, 11, 12, 128)))
+        );
+        assert_eq!(
+            Value::Vec2(Vec2::new(0.0, 0.0)).parse_like("(1, -2.5)"),
+            Ok(Value::Vec2(Vec2::new(1.0, -2.5)))
+        );
+        assert!(Value::Int(0).parse_like("1.5").is_err());
+        assert!(Value::Color(RGBA8::default()).parse_like("#12345").is_err());
+        assert!(Value::Color(RGBA8::default()).parse_like("#ééé").is_err());
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut params = Params::new();
+        params
+            .add_float("speed", 0.25, 0.0, 1.0)
+            .add_int("count", 3, 0, 10)
+            .add_bool("trails", true)
+            .add_color("tint", RGBA8::new(1, 2, 3, 255))
+            .add_vec2("offset", Vec2::new(1.0, -1.0), -5.0, 5.0);
+        let text = params.to_text();
+        assert_eq!(
+            text,
+            "speed = 0.25\ncount = 3\ntrails = true\ntint = #010203\noffset = (1, -1)\n"
+        );
+        let mut copy = Params::new();
+        copy.add_float("speed", 0.0, 0.0, 1.0)
+            .add_int("count", 0, 0, 10)
+            .add_bool("trails", false)
+            .add_color("tint", RGBA8::default())
+            .add_vec2("offset", Vec2::new(0.0, 0.0), -5.0, 5.0);
+        let (changed, errors) = copy.apply_text(&text);
+        assert_eq!(changed, ["speed", "count", "trails", "tint", "offset"]);
+        assert!(errors.is_empty());
+        assert_eq!(copy.to_text(), text);
+    }
+
+    #[test]
+    fn apply_text_reports_bad_lines_and_goes_on() {
+        let mut params = Params::new();
+        params
+            .add_float("speed", 0.5, 0.0, 1.0)
+            .add_bool("trails", false);
+        let text = "# comment\n\nspeed = 0.5\nnonsense\nmissing = 1\ntrails = maybe\ntrails=true\n";
+        let (changed, errors) = params.apply_text(text);
+        assert_eq!(changed, ["trails"]);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("line 4:"), "{errors:?}");
+        assert!(errors[1].starts_with("line 5:"), "{errors:?}");
+        assert!(errors[2].starts_with("line 6:"), "{errors:?}");
+    }
+
+    #[test]
+    fn loaded_values_are_clamped_to_the_range() {
+        let mut params = Params::new();
+        params
+            .add_float("speed", 0.0, -0.2, 0.2)
+            .add_int("count", 0, 0, 10)
+            .add_vec2("offset", Vec2::new(0.0, 0.0), -1.0, 1.0)
+            .add_color("tint", RGBA8::default());
+        assert_eq!(params.set_from_str("speed", "1e9"), Ok(true));
+        assert_eq!(params.float("speed").to_bits(), 0.2_f32.to_bits());
+        assert_eq!(params.set_from_str("count", "-3"), Ok(false));
+        assert_eq!(params.int("count"), 0);
+        params.set_from_str("offset", "(5, -0.5)").unwrap();
+        assert_eq!(params.vec2("offset"), Vec2::new(1.0, -0.5));
+        params.set_from_str("tint", "#ffffff").unwrap();
+        assert_eq!(params.color("tint"), RGBA8::new(255, 255, 255, 255));
+    }
+
+    #[test]
+    fn file_changes_are_applied_on_poll() {
+        let path = std::env::temp_dir().join(format!("params-test-{}.txt", std::process::id()));
+        let mut params = Params::new();
+        params.add_float("speed", 0.0, 0.0, 1.0);
+        let mut file = ParamsFile::new(&path);
+        // Nothing there yet, nothing happens.
+        file.poll(&mut params);
+        fs::write(&path, "speed = 0.75\n").unwrap();
+        file.last_poll = None;
+        file.poll(&mut params);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(params.float("speed").to_bits(), 0.75_f32.to_bits());
+    }
+}
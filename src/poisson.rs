@@ -0,0 +1,241 @@
+//! Poisson-disk sampling (Bridson's algorithm): evenly spread random points that never clump.
+
+use crate::geometry::Rect;
+use crate::math::Vec2;
+use crate::random::Random;
+
+/// Candidates tried around an active point before it is retired.
+const ATTEMPTS: u32 = 30;
+
+/// Points inside `rect` that are at least `radius` apart.
+pub fn poisson_disk(random: &Random, rect: &Rect, radius: f32) -> Vec<Vec2> {
+    poisson_disk_in(random, rect, radius, radius, |_| 1.0, |_| true)
+}
+
+/// Points inside of the region described by `contains`, searched for within `bounds`.
+pub fn poisson_disk_region(
+    random: &Random,
+    bounds: &Rect,
+    radius: f32,
+    contains: impl Fn(Vec2) -> bool,
+) -> Vec<Vec2> {
+    poisson_disk_in(random, bounds, radius, radius, |_| 1.0, contains)
+}
+
+/// Variable density sampling: `density` maps a position to [0, 1], where 1 packs points
+/// `min_radius` apart and 0 spaces them `max_radius` apart.
+/// Only points for which `contains` is true are kept.
+#[allow(clippy::suboptimal_flops)]
+pub fn poisson_disk_in(
+    random: &Random,
+    bounds: &Rect,
+    min_radius: f32,
+    max_radius: f32,
+    density: impl Fn(Vec2) -> f32,
+    contains: impl Fn(Vec2) -> bool,
+) -> Vec<Vec2> {
+    assert!(
+        min_radius > 0.0 && min_radius <= max_radius,
+        "Poisson disk radii should be positive, with min_radius <= max_radius"
+    );
+    let radius_at = |point: Vec2| {
+        let density = density(point).clamp(0.0, 1.0);
+        max_radius + (min_radius - max_radius) * density
+    };
+    let mut grid = Grid::new(bounds, min_radius, max_radius);
+    let mut active: Vec<usize> = Vec::new();
+
+    loop {
+        // Seed a new point whenever the active list runs dry, that also reaches disconnected parts of a region.
+        let seed = (0..ATTEMPTS)
+            .map(|_| grid.random_point(random))
+            .find(|point| contains(*point) && grid.is_free(*point, radius_at(*point)));
+        let Some(seed) = seed else {
+            break;
+        };
+        active.push(grid.insert(seed, radius_at(seed)));
+
+        while !active.is_empty() {
+            let active_index = random.random_range(0.0, active.len() as f32) as usize;
+            let active_index = active_index.min(active.len() - 1);
+            let (center, radius) = grid.points[active[active_index]];
+
+            let found = (0..ATTEMPTS)
+                .map(|_| {
+                    // Uniform over the area of the annulus between radius and 2 * radius.
+                    let distance = (radius * radius * random.random_range(1.0, 4.0)).sqrt();
+                    random.random_on_circle(center, distance)
+                })
+                .find(|candidate| {
+                    grid.in_bounds(*candidate)
+                        && contains(*candidate)
+                        && grid.is_free(*candidate, radius_at(*candidate))
+                });
+            match found {
+                Some(point) => active.push(grid.insert(point, radius_at(point))),
+                None => {
+                    active.swap_remove(active_index);
+                }
+            }
+        }
+    }
+
+    grid.points.into_iter().map(|(point, _)| point).collect()
+}
+
+/// Background grid so neighbours are found without looking at every point.
+/// Cells are small enough that each one holds at most one point.
+struct Grid {
+    origin: Vec2,
+    size: Vec2,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Option<usize>>,
+    /// Accepted points with the radius they keep clear.
+    points: Vec<(Vec2, f32)>,
+    /// How many cells away a conflicting point can be.
+    reach: i32,
+}
+
+impl Grid {
+    fn new(bounds: &Rect, min_radius: f32, max_radius: f32) -> Self {
+        let size = Vec2::new(bounds.size.width as f32, bounds.size.height as f32);
+        let cell_size = min_radius / std::f32::consts::SQRT_2;
+        let columns = (size.x / cell_size).ceil() as usize + 1;
+        let rows = (size.y / cell_size).ceil() as usize + 1;
+        Self {
+            origin: bounds.origin,
+            size,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![None; columns * rows],
+            points: Vec::new(),
+            reach: (max_radius / cell_size).ceil() as i32,
+        }
+    }
+
+    fn random_point(&self, random: &Random) -> Vec2 {
+        self.origin
+            + Vec2::new(
+                random.random_range(0.0, self.size.x),
+                random.random_range(0.0, self.size.y),
+            )
+    }
+
+    fn in_bounds(&self, point: Vec2) -> bool {
+        let local = point - self.origin;
+        local.x >= 0.0 && local.y >= 0.0 && local.x < self.size.x && local.y < self.size.y
+    }
+
+    fn cell(&self, point: Vec2) -> (i32, i32) {
+        let local = (point - self.origin) / self.cell_size;
+        (local.x as i32, local.y as i32)
+    }
+
+    /// No accepted point is closer than the larger of the two radii.
+    fn is_free(&self, point: Vec2, radius: f32) -> bool {
+        let (column, row) = self.cell(point);
+        for y in (row - self.reach).max(0)..=(row + self.reach).min(self.rows as i32 - 1) {
+            for x in
+                (column - self.reach).max(0)..=(column + self.reach).min(self.columns as i32 - 1)
+            {
+                if let Some(index) = self.cells[y as usize * self.columns + x as usize] {
+                    let (other, other_radius) = self.points[index];
+                    let clearance = radius.max(other_radius);
+                    if point.distance_squared(other) < clearance * clearance {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn insert(&mut self, point: Vec2, radius: f32) -> usize {
+        let (column, row) = self.cell(point);
+        let index = self.points.len();
+        self.points.push((point, radius));
+        self.cells[row as usize * self.columns + column as usize] = Some(index);
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+
+    fn rect() -> Rect {
+        Rect::new(Vec2::new(10.0, 20.0), Size::new(100, 60))
+    }
+
+    fn inside(rect: &Rect, point: Vec2) -> bool {
+        point.x >= rect.origin.x
+            && point.y >= rect.origin.y
+            && point.x < rect.origin.x + rect.size.width as f32
+            && point.y < rect.origin.y + rect.size.height as f32
+    }
+
+    #[test]
+    fn points_keep_their_distance() {
+        let rect = rect();
+        let points = poisson_disk(&Random::new(1), &rect, 5.0);
+        // 6000 square units at one point per ~2 r^2 leaves plenty, a broken sampler gives far fewer.
+        assert!(points.len() > 60, "{}", points.len());
+        for (i, a) in points.iter().enumerate() {
+            assert!(inside(&rect, *a), "{a:?}");
+            for b in &points[i + 1..] {
+                assert!(a.distance(*b) >= 5.0, "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_points() {
+        let a = poisson_disk(&Random::new(7), &rect(), 4.0);
+        let b = poisson_disk(&Random::new(7), &rect(), 4.0);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn region_reaches_disconnected_parts() {
+        let rect = rect();
+        let left = |p: Vec2| p.x < 30.0;
+        let right = |p: Vec2| p.x > 90.0;
+        let points = poisson_disk_region(&Random::new(3), &rect, 3.0, |p| left(p) || right(p));
+        assert!(points.iter().all(|p| left(*p) || right(*p)));
+        assert!(points.iter().any(|p| left(*p)));
+        assert!(points.iter().any(|p| right(*p)));
+    }
+
+    #[test]
+    fn density_packs_points_closer() {
+        let rect = rect();
+        // Dense on the left half, sparse on the right half.
+        let split = rect.origin.x + 50.0;
+        let points = poisson_disk_in(
+            &Random::new(5),
+            &rect,
+            2.0,
+            8.0,
+            |p| if p.x < split { 1.0 } else { 0.0 },
+            |_| true,
+        );
+        let dense = points.iter().filter(|p| p.x < split).count();
+        let sparse = points.len() - dense;
+        assert!(dense > sparse * 4, "{dense} {sparse}");
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[i + 1..] {
+                assert!(a.distance(*b) >= 2.0);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "min_radius <= max_radius")]
+    fn rejects_swapped_radii() {
+        poisson_disk_in(&Random::new(0), &rect(), 4.0, 2.0, |_| 1.0, |_| true);
+    }
+}
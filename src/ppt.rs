@@ -1,3 +1,6 @@
+//! Basic image saving/loading with ppt format.
+
+use std::fmt::Write;
 use std::fs;
 use std::io;
 use std::path::Path;
@@ -8,13 +11,11 @@ use crate::geometry::Size;
 use crate::math::Vec2;
 use crate::sprite::Sprite;
 
-/// Basic image saving/loading with ppt format.
-
 pub fn save_sprite<P: AsRef<Path>>(path: P, sprite: &Sprite) -> Result<(), io::Error> {
     let (width, height) = (sprite.size.width, sprite.size.height);
     let mut data: String = String::with_capacity(sprite.pixels.len() * 3 * 4); // 3 colors, 4 chars per color string (max)
     for pixel in &sprite.pixels {
-        data.push_str(&format!("{} {} {} ", pixel.r, pixel.g, pixel.b));
+        let _ = write!(data, "{} {} {} ", pixel.r, pixel.g, pixel.b);
     }
     let ppt_header = format!("P3 {width} {height} 255\n");
     fs::write(path, [ppt_header, data].concat())
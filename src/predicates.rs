@@ -0,0 +1,263 @@
+//! Robust geometric predicates after Shewchuk: a quick floating point answer when it is
+//! certainly right, and an exact answer from expansion arithmetic when it might not be.
+//!
+//! The points are widened to f64, so every f32 input is represented exactly.
+
+use std::cmp::Ordering;
+
+use crate::math::Vec2;
+
+/// Half an ulp of 1.0 in f64.
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const ORIENT_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const INCIRCLE_BOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+/// Which side of the line through `a` and `b` the point `c` is on.
+/// `Greater` when `a`, `b`, `c` turn counter-clockwise with y up (clockwise on screen),
+/// `Less` for the other way and `Equal` only when the points are exactly collinear.
+pub fn orient2d(a: Vec2, b: Vec2, c: Vec2) -> Ordering {
+    let [ax, ay, bx, by, cx, cy] = widen([a, b, c]);
+    let left = (ax - cx) * (by - cy);
+    let right = (ay - cy) * (bx - cx);
+    let determinant = left - right;
+    if determinant.abs() >= ORIENT_BOUND * (left.abs() + right.abs()) {
+        return sign(determinant);
+    }
+
+    let acx = two_diff(ax, cx);
+    let bcy = two_diff(by, cy);
+    let acy = two_diff(ay, cy);
+    let bcx = two_diff(bx, cx);
+    let exact = sum(&product(&acx, &bcy), &negate(&product(&acy, &bcx)));
+    expansion_sign(&exact)
+}
+
+/// Whether `d` is inside the circle through `a`, `b` and `c`.
+/// `Greater` for inside when `a`, `b`, `c` are counter-clockwise (see `orient2d`), the
+/// answer flips for clockwise triangles. `Equal` only when all four are exactly cocircular.
+// The filter's error bound counts every product as rounded on its own, which `mul_add` isn't.
+#[allow(clippy::suboptimal_flops)]
+pub fn incircle(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Ordering {
+    let [ax, ay, bx, by, cx, cy] = widen([a, b, c]);
+    let (dx, dy) = (f64::from(d.x), f64::from(d.y));
+    let (adx, ady) = (ax - dx, ay - dy);
+    let (bdx, bdy) = (bx - dx, by - dy);
+    let (cdx, cdy) = (cx - dx, cy - dy);
+
+    let (bc_left, bc_right) = (bdx * cdy, cdx * bdy);
+    let (ca_left, ca_right) = (cdx * ady, adx * cdy);
+    let (ab_left, ab_right) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+    let determinant =
+        alift * (bc_left - bc_right) + blift * (ca_left - ca_right) + clift * (ab_left - ab_right);
+    let permanent = (bc_left.abs() + bc_right.abs()) * alift
+        + (ca_left.abs() + ca_right.abs()) * blift
+        + (ab_left.abs() + ab_right.abs()) * clift;
+    if determinant.abs() >= INCIRCLE_BOUND * permanent {
+        return sign(determinant);
+    }
+
+    let adx = two_diff(ax, dx);
+    let ady = two_diff(ay, dy);
+    let bdx = two_diff(bx, dx);
+    let bdy = two_diff(by, dy);
+    let cdx = two_diff(cx, dx);
+    let cdy = two_diff(cy, dy);
+    let lift = |x: &[f64], y: &[f64]| sum(&product(x, x), &product(y, y));
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]| {
+        sum(&product(x1, y2), &negate(&product(y1, x2)))
+    };
+    let a_term = product(&lift(&adx, &ady), &cross(&bdx, &bdy, &cdx, &cdy));
+    let b_term = product(&lift(&bdx, &bdy), &cross(&cdx, &cdy, &adx, &ady));
+    let c_term = product(&lift(&cdx, &cdy), &cross(&adx, &ady, &bdx, &bdy));
+    expansion_sign(&sum(&sum(&a_term, &b_term), &c_term))
+}
+
+fn widen([a, b, c]: [Vec2; 3]) -> [f64; 6] {
+    [a.x, a.y, b.x, b.y, c.x, c.y].map(f64::from)
+}
+
+fn sign(value: f64) -> Ordering {
+    value.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+}
+
+// Expansions are sums of non-overlapping f64 components, ordered from the smallest magnitude,
+// so they hold results exactly. Zero components are dropped along the way.
+
+/// `a + b` as the rounded sum and its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+/// `a - b` exactly.
+fn two_diff(a: f64, b: f64) -> Vec<f64> {
+    let (x, error) = two_sum(a, -b);
+    [error, x].into_iter().filter(|c| *c != 0.0).collect()
+}
+
+/// `a * b` as the rounded product and its rounding error.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// Adds a single value to an expansion.
+fn grow(expansion: &[f64], value: f64) -> Vec<f64> {
+    let mut result = Vec::with_capacity(expansion.len() + 1);
+    let mut carry = value;
+    for &component in expansion {
+        let (x, error) = two_sum(carry, component);
+        if error != 0.0 {
+            result.push(error);
+        }
+        carry = x;
+    }
+    if carry != 0.0 {
+        result.push(carry);
+    }
+    result
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter()
+        .fold(e.to_vec(), |total, &value| grow(&total, value))
+}
+
+fn scale(expansion: &[f64], factor: f64) -> Vec<f64> {
+    expansion.iter().fold(Vec::new(), |total, &component| {
+        let (x, error) = two_product(component, factor);
+        grow(&grow(&total, error), x)
+    })
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter()
+        .fold(Vec::new(), |total, &factor| sum(&total, &scale(e, factor)))
+}
+
+fn negate(expansion: &[f64]) -> Vec<f64> {
+    expansion.iter().map(|component| -component).collect()
+}
+
+/// The largest component decides the sign of the whole expansion.
+fn expansion_sign(expansion: &[f64]) -> Ordering {
+    expansion
+        .last()
+        .map_or(Ordering::Equal, |largest| sign(*largest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every f32 at or above 8 is a whole multiple of 2^-20, so scaled coordinates are exact
+    /// integers and the reference determinants can be computed without rounding.
+    fn exact(value: f32) -> i128 {
+        assert!(value >= 8.0);
+        let scaled = f64::from(value) * f64::from(1 << 20);
+        assert_eq!(
+            scaled.fract().to_bits(),
+            0,
+            "{value} isn't a multiple of 2^-20"
+        );
+        scaled as i128
+    }
+
+    fn reference_orient(a: Vec2, b: Vec2, c: Vec2) -> Ordering {
+        let [ax, ay, bx, by, cx, cy] = [a.x, a.y, b.x, b.y, c.x, c.y].map(exact);
+        ((ax - cx) * (by - cy) - (ay - cy) * (bx - cx)).cmp(&0)
+    }
+
+    fn reference_incircle(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Ordering {
+        let [ax, ay, bx, by, cx, cy] = [a.x, a.y, b.x, b.y, c.x, c.y].map(exact);
+        let (dx, dy) = (exact(d.x), exact(d.y));
+        let lift = |x: i128, y: i128| x * x + y * y;
+        let cross = |x1: i128, y1: i128, x2: i128, y2: i128| x1 * y2 - y1 * x2;
+        let (adx, ady, bdx, bdy, cdx, cdy) = (ax - dx, ay - dy, bx - dx, by - dy, cx - dx, cy - dy);
+        let determinant = lift(adx, ady) * cross(bdx, bdy, cdx, cdy)
+            + lift(bdx, bdy) * cross(cdx, cdy, adx, ady)
+            + lift(cdx, cdy) * cross(adx, ady, bdx, bdy);
+        determinant.cmp(&0)
+    }
+
+    /// `value` moved by `steps` representable floats.
+    fn nudge(value: f32, steps: i32) -> f32 {
+        f32::from_bits(value.to_bits().wrapping_add_signed(steps))
+    }
+
+    #[test]
+    fn orient_is_exact_near_a_line() {
+        let (b, c) = (Vec2::new(12.0, 12.0), Vec2::new(14.5, 14.5));
+        let mut seen = [0; 3];
+        for i in -16..=16 {
+            for j in -16..=16 {
+                let a = Vec2::new(nudge(9.5, i), nudge(9.5, j));
+                let expected = reference_orient(a, b, c);
+                assert_eq!(orient2d(a, b, c), expected, "{i} {j}");
+                assert_eq!(orient2d(b, a, c), expected.reverse(), "{i} {j}");
+                seen[(expected as i8 + 1) as usize] += 1;
+            }
+        }
+        // The grid straddles the line, so all three answers come up.
+        assert!(seen.iter().all(|count| *count > 0), "{seen:?}");
+    }
+
+    #[test]
+    fn orient_direction() {
+        let (a, b) = (Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+        assert_eq!(orient2d(a, b, Vec2::new(0.5, 1.0)), Ordering::Greater);
+        assert_eq!(orient2d(a, b, Vec2::new(0.5, -1.0)), Ordering::Less);
+        assert_eq!(orient2d(a, b, Vec2::new(7.0, 0.0)), Ordering::Equal);
+    }
+
+    #[test]
+    fn incircle_is_exact_near_a_circle() {
+        // Counter-clockwise points on the circle of radius 5 around (12, 12).
+        let a = Vec2::new(17.0, 12.0);
+        let b = Vec2::new(12.0, 17.0);
+        let c = Vec2::new(9.0, 8.0);
+        let mut seen = [0; 3];
+        for i in -8..=8 {
+            for j in -8..=8 {
+                let d = Vec2::new(nudge(15.0, i), nudge(16.0, j));
+                let expected = reference_incircle(a, b, c, d);
+                assert_eq!(incircle(a, b, c, d), expected, "{i} {j}");
+                // Clockwise order flips the answer.
+                assert_eq!(incircle(b, a, c, d), expected.reverse(), "{i} {j}");
+                seen[(expected as i8 + 1) as usize] += 1;
+            }
+        }
+        assert!(seen.iter().all(|count| *count > 0), "{seen:?}");
+    }
+
+    #[test]
+    fn incircle_direction() {
+        let (a, b, c) = (
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        );
+        assert_eq!(incircle(a, b, c, Vec2::new(1.0, 1.0)), Ordering::Greater);
+        assert_eq!(incircle(a, b, c, Vec2::new(3.0, 3.0)), Ordering::Less);
+        assert_eq!(incircle(a, b, c, Vec2::new(2.0, 2.0)), Ordering::Equal);
+    }
+
+    #[test]
+    fn expansions_are_exact() {
+        let big = 1e20;
+        let expansion = sum(&two_diff(big, 1.0), &two_diff(-big, -1.0));
+        assert!(expansion.is_empty());
+        let squared = product(&two_diff(big, 1.0), &two_diff(big, 1.0));
+        // (1e20 - 1)^2 keeps the -2e20 + 1 that a plain f64 loses.
+        assert_eq!(
+            expansion_sign(&sum(&squared, &[-big * big])),
+            Ordering::Less
+        );
+        assert_eq!(expansion_sign(&[]), Ordering::Equal);
+    }
+}
@@ -0,0 +1,165 @@
+use std::f32::consts::TAU;
+
+use fastrand::Rng;
+
+use crate::math::Vec2;
+
+/// Seeded source of randomness for sketches, the same seed always produces the same sequence.
+pub struct Random {
+    rng: Rng,
+    seed: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::with_seed(seed),
+            seed,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts the sequence over with a new seed.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng.seed(seed);
+    }
+
+    /// Uniform in [0, 1)
+    pub fn random(&self) -> f32 {
+        self.rng.f32()
+    }
+
+    /// Uniform in [min, max)
+    #[allow(clippy::suboptimal_flops)]
+    pub fn random_range(&self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.rng.f32()
+    }
+
+    /// Normally distributed, using the Box-Muller transform.
+    #[allow(clippy::suboptimal_flops)]
+    pub fn random_gaussian(&self, mean: f32, standard_deviation: f32) -> f32 {
+        // 1 - f32() is in (0, 1], so the logarithm is finite.
+        let u1 = 1.0 - self.rng.f32();
+        let u2 = self.rng.f32();
+        let magnitude = (-2.0 * u1.ln()).sqrt();
+        mean + standard_deviation * magnitude * (TAU * u2).cos()
+    }
+
+    pub fn random_choice<'a, T>(&self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.rng.usize(..items.len()))
+    }
+
+    /// Picks an item with probability proportional to its weight, negative weights count as 0.
+    pub fn random_weighted<'a, T>(&self, items: &'a [(T, f32)]) -> Option<&'a T> {
+        let total: f32 = items.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.rng.f32() * total;
+        for (item, weight) in items {
+            let weight = weight.max(0.0);
+            if target < weight {
+                return Some(item);
+            }
+            target -= weight;
+        }
+        // Float error can leave a sliver past the last item.
+        items
+            .iter()
+            .rev()
+            .find(|(_, weight)| *weight > 0.0)
+            .map(|(item, _)| item)
+    }
+
+    /// Uniformly distributed over the area of the circle.
+    pub fn random_in_circle(&self, center: Vec2, radius: f32) -> Vec2 {
+        let distance = radius * self.rng.f32().sqrt();
+        center + Vec2::from_angle(self.rng.f32() * TAU) * distance
+    }
+
+    pub fn random_on_circle(&self, center: Vec2, radius: f32) -> Vec2 {
+        center + Vec2::from_angle(self.rng.f32() * TAU) * radius
+    }
+
+    pub fn shuffle<T>(&self, items: &mut [T]) {
+        self.rng.shuffle(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let (first, second) = (Random::new(7), Random::new(7));
+        let draws = |random: &Random| (0..16).map(|_| random.random()).collect::<Vec<_>>();
+        assert_eq!(draws(&first), draws(&second));
+
+        let mut reseeded = Random::new(1);
+        let before = draws(&reseeded);
+        reseeded.reseed(1);
+        assert_eq!(draws(&reseeded), before);
+        assert_eq!(reseeded.seed(), 1);
+    }
+
+    #[test]
+    fn ranges_stay_inside() {
+        let random = Random::new(3);
+        for _ in 0..1000 {
+            let value = random.random_range(-2.0, 5.0);
+            assert!((-2.0..5.0).contains(&value));
+            let center = Vec2::new(10.0, -4.0);
+            assert!(random.random_in_circle(center, 3.0).distance(center) <= 3.0 + 1e-4);
+            let on = random.random_on_circle(center, 3.0).distance(center);
+            assert!((on - 3.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn gaussian_has_the_requested_mean_and_deviation() {
+        let random = Random::new(11);
+        let samples: Vec<f32> = (0..20_000)
+            .map(|_| random.random_gaussian(5.0, 2.0))
+            .collect();
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let variance =
+            samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / samples.len() as f32;
+        assert!((mean - 5.0).abs() < 0.1, "{mean}");
+        assert!((variance.sqrt() - 2.0).abs() < 0.1, "{variance}");
+    }
+
+    #[test]
+    fn weighted_choice_skips_zero_and_negative_weights() {
+        let random = Random::new(5);
+        assert_eq!(random.random_weighted::<u8>(&[]), None);
+        assert_eq!(random.random_weighted(&[('a', 0.0), ('b', -1.0)]), None);
+        let items = [('a', 0.0), ('b', 3.0), ('c', -2.0), ('d', 1.0)];
+        let mut counts = [0; 4];
+        for _ in 0..4000 {
+            let picked = random.random_weighted(&items).unwrap();
+            counts[items.iter().position(|(item, _)| item == picked).unwrap()] += 1;
+        }
+        assert_eq!((counts[0], counts[2]), (0, 0));
+        let ratio = f64::from(counts[1]) / f64::from(counts[3]);
+        assert!((2.5..3.5).contains(&ratio), "{ratio}");
+    }
+
+    #[test]
+    fn choice_and_shuffle_keep_the_items() {
+        let random = Random::new(9);
+        assert_eq!(random.random_choice::<u8>(&[]), None);
+        assert!([1, 2, 3].contains(random.random_choice(&[1, 2, 3]).unwrap()));
+        let mut items: Vec<u32> = (0..50).collect();
+        random.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+    }
+}
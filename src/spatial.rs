@@ -0,0 +1,789 @@
+//! Spatial indexes to find things near each other without checking every pair.
+//! Items are ids picked by the caller, usually indices into their own list,
+//! each placed at a point or covering a rectangle.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::geometry::Rect;
+use crate::graphics::line;
+use crate::math::Vec2;
+
+/// Axis-aligned box in floats, `Rect` sits on whole pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Aabb {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Aabb {
+    fn point(point: Vec2) -> Self {
+        Self {
+            min: point,
+            max: point,
+        }
+    }
+
+    fn from_rect(rect: &Rect) -> Self {
+        Self {
+            min: rect.origin,
+            max: rect.bottom_right(),
+        }
+    }
+
+    fn around(center: Vec2, radius: f32) -> Self {
+        let offset = Vec2::new(radius, radius);
+        Self {
+            min: center - offset,
+            max: center + offset,
+        }
+    }
+
+    fn contains(&self, other: &Self) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && other.max.x <= self.max.x
+            && other.max.y <= self.max.y
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// Zero for points inside.
+    fn distance_squared(&self, point: Vec2) -> f32 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx * dx + dy * dy
+    }
+
+    fn outline(&self) -> Vec<Vec2> {
+        let (top_right, bottom_left) = (
+            Vec2::new(self.max.x, self.min.y),
+            Vec2::new(self.min.x, self.max.y),
+        );
+        let mut points = line(self.min, top_right);
+        points.extend(line(top_right, self.max));
+        points.extend(line(self.max, bottom_left));
+        points.extend(line(bottom_left, self.min));
+        points
+    }
+}
+
+/// Tree of quarters that split once they hold more than `capacity` items. Rectangles stay
+/// in the smallest node that fits them whole. Items outside the bounds are kept by the root,
+/// they work but are always looked at.
+#[derive(Clone, Debug)]
+pub struct Quadtree {
+    root: Node,
+    items: HashMap<usize, Aabb>,
+    capacity: usize,
+    max_depth: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Aabb,
+    depth: usize,
+    items: Vec<(usize, Aabb)>,
+    children: Option<Box<[Self; 4]>>,
+}
+
+impl Quadtree {
+    pub fn new(bounds: &Rect) -> Self {
+        Self {
+            root: Node::new(Aabb::from_rect(bounds), 0),
+            items: HashMap::new(),
+            capacity: 8,
+            max_depth: 8,
+        }
+    }
+
+    pub fn capacity(self, capacity: usize) -> Self {
+        assert!(capacity > 0, "Quadtree nodes should hold at least one item");
+        Self { capacity, ..self }
+    }
+
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Adds the item at `point`, an id that is already in replaces the old one.
+    pub fn insert(&mut self, id: usize, point: Vec2) {
+        self.insert_aabb(id, Aabb::point(point));
+    }
+
+    pub fn insert_rect(&mut self, id: usize, rect: &Rect) {
+        self.insert_aabb(id, Aabb::from_rect(rect));
+    }
+
+    /// Whether the id was in.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let Some(aabb) = self.items.remove(&id) else {
+            return false;
+        };
+        self.root.remove(id, &aabb, self.capacity);
+        true
+    }
+
+    pub fn move_to(&mut self, id: usize, point: Vec2) {
+        self.insert(id, point);
+    }
+
+    pub fn move_rect(&mut self, id: usize, rect: &Rect) {
+        self.insert_rect(id, rect);
+    }
+
+    /// Items touching the rectangle.
+    pub fn query_rect(&self, rect: &Rect) -> Vec<usize> {
+        let area = Aabb::from_rect(rect);
+        let mut found = Vec::new();
+        self.root.visit(&area, &mut |id, aabb| {
+            if aabb.intersects(&area) {
+                found.push(id);
+            }
+        });
+        found
+    }
+
+    /// Items at most `radius` away from `center`.
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.root
+            .visit(&Aabb::around(center, radius), &mut |id, aabb| {
+                if aabb.distance_squared(center) <= radius * radius {
+                    found.push(id);
+                }
+            });
+        found
+    }
+
+    /// Up to `count` items closest to `point`, closest first. Nodes are opened in order of
+    /// distance, so the search stops as soon as no closer item can be left.
+    pub fn nearest(&self, point: Vec2, count: usize) -> Vec<usize> {
+        let mut queue = BinaryHeap::new();
+        queue.push(Queued {
+            distance: 0.0,
+            entry: Entry::Node(&self.root),
+        });
+        let mut found = Vec::with_capacity(count);
+        while found.len() < count {
+            let Some(Queued { entry, .. }) = queue.pop() else {
+                break;
+            };
+            match entry {
+                Entry::Item(id) => found.push(id),
+                Entry::Node(node) => {
+                    for &(id, aabb) in &node.items {
+                        queue.push(Queued {
+                            distance: aabb.distance_squared(point),
+                            entry: Entry::Item(id),
+                        });
+                    }
+                    for child in node.children.iter().flat_map(|children| children.iter()) {
+                        queue.push(Queued {
+                            distance: child.bounds.distance_squared(point),
+                            entry: Entry::Node(child),
+                        });
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Outlines of every node, to see how the tree splits.
+    pub fn empty(&self) -> Vec<Vec2> {
+        let mut points = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            points.extend(node.bounds.outline());
+            stack.extend(node.children.iter().flat_map(|children| children.iter()));
+        }
+        points
+    }
+
+    fn insert_aabb(&mut self, id: usize, aabb: Aabb) {
+        if let Some(old) = self.items.insert(id, aabb) {
+            self.root.remove(id, &old, self.capacity);
+        }
+        self.root.insert(id, aabb, self.capacity, self.max_depth);
+    }
+}
+
+impl Node {
+    fn new(bounds: Aabb, depth: usize) -> Self {
+        Self {
+            bounds,
+            depth,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn child_for(&mut self, aabb: &Aabb) -> Option<&mut Self> {
+        self.children
+            .as_mut()?
+            .iter_mut()
+            .find(|child| child.bounds.contains(aabb))
+    }
+
+    fn insert(&mut self, id: usize, aabb: Aabb, capacity: usize, max_depth: usize) {
+        if let Some(child) = self.child_for(&aabb) {
+            child.insert(id, aabb, capacity, max_depth);
+            return;
+        }
+        self.items.push((id, aabb));
+        if self.children.is_none() && self.items.len() > capacity && self.depth < max_depth {
+            self.split(capacity, max_depth);
+        }
+    }
+
+    fn split(&mut self, capacity: usize, max_depth: usize) {
+        let Aabb { min, max } = self.bounds;
+        let middle = (min + max) / 2.0;
+        let depth = self.depth + 1;
+        self.children = Some(Box::new([
+            Self::new(Aabb { min, max: middle }, depth),
+            Self::new(
+                Aabb {
+                    min: Vec2::new(middle.x, min.y),
+                    max: Vec2::new(max.x, middle.y),
+                },
+                depth,
+            ),
+            Self::new(
+                Aabb {
+                    min: Vec2::new(min.x, middle.y),
+                    max: Vec2::new(middle.x, max.y),
+                },
+                depth,
+            ),
+            Self::new(Aabb { min: middle, max }, depth),
+        ]));
+        for (id, aabb) in std::mem::take(&mut self.items) {
+            self.insert(id, aabb, capacity, max_depth);
+        }
+    }
+
+    /// Follows the same path as `insert`, then merges children that got too empty.
+    fn remove(&mut self, id: usize, aabb: &Aabb, capacity: usize) -> bool {
+        if let Some(index) = self.items.iter().position(|(item, _)| *item == id) {
+            self.items.swap_remove(index);
+            return true;
+        }
+        let removed = self
+            .child_for(aabb)
+            .is_some_and(|child| child.remove(id, aabb, capacity));
+        if removed {
+            self.merge(capacity);
+        }
+        removed
+    }
+
+    fn merge(&mut self, capacity: usize) {
+        let Some(children) = &self.children else {
+            return;
+        };
+        let leaves = children.iter().all(|child| child.children.is_none());
+        let count = self.items.len()
+            + children
+                .iter()
+                .map(|child| child.items.len())
+                .sum::<usize>();
+        if leaves && count <= capacity {
+            let children = self.children.take().expect("checked above");
+            for child in children.into_iter() {
+                self.items.extend(child.items);
+            }
+        }
+    }
+
+    /// Calls `found` with every item in nodes that touch `area`.
+    fn visit(&self, area: &Aabb, found: &mut impl FnMut(usize, &Aabb)) {
+        for (id, aabb) in &self.items {
+            found(*id, aabb);
+        }
+        for child in self.children.iter().flat_map(|children| children.iter()) {
+            if child.bounds.intersects(area) {
+                child.visit(area, found);
+            }
+        }
+    }
+}
+
+enum Entry<'a> {
+    Node(&'a Node),
+    Item(usize),
+}
+
+/// Queue entry for `nearest`, the heap pops the smallest distance first.
+struct Queued<'a> {
+    distance: f32,
+    entry: Entry<'a>,
+}
+
+impl PartialEq for Queued<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued<'_> {}
+
+impl PartialOrd for Queued<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Items before nodes at the same distance, a node can't hold anything closer.
+        let is_node = |queued: &Self| matches!(queued.entry, Entry::Node(_));
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| is_node(other).cmp(&is_node(self)))
+    }
+}
+
+/// Uniform grid keyed by cell, only the cells in use take memory. Works best with
+/// `cell_size` around the usual query radius.
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    items: HashMap<usize, Aabb>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "Spatial hash cells should have a positive size"
+        );
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Adds the item at `point`, an id that is already in replaces the old one.
+    pub fn insert(&mut self, id: usize, point: Vec2) {
+        self.insert_aabb(id, Aabb::point(point));
+    }
+
+    /// The item goes into every cell the rectangle touches.
+    pub fn insert_rect(&mut self, id: usize, rect: &Rect) {
+        self.insert_aabb(id, Aabb::from_rect(rect));
+    }
+
+    /// Whether the id was in.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let Some(aabb) = self.items.remove(&id) else {
+            return false;
+        };
+        for cell in self.cells_of(&aabb) {
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                ids.retain(|item| *item != id);
+                if ids.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        true
+    }
+
+    pub fn move_to(&mut self, id: usize, point: Vec2) {
+        self.insert(id, point);
+    }
+
+    pub fn move_rect(&mut self, id: usize, rect: &Rect) {
+        self.insert_rect(id, rect);
+    }
+
+    /// Items touching the rectangle.
+    pub fn query_rect(&self, rect: &Rect) -> Vec<usize> {
+        let area = Aabb::from_rect(rect);
+        self.candidates(&area)
+            .into_iter()
+            .filter(|id| self.items[id].intersects(&area))
+            .collect()
+    }
+
+    /// Items at most `radius` away from `center`.
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<usize> {
+        self.candidates(&Aabb::around(center, radius))
+            .into_iter()
+            .filter(|id| self.items[id].distance_squared(center) <= radius * radius)
+            .collect()
+    }
+
+    /// Up to `count` items closest to `point`, closest first. Rings of cells are searched
+    /// outwards until the next ring can't hold anything closer. Once the rings have probed
+    /// more cells than are in use, the point is far from the items and they are sorted instead.
+    pub fn nearest(&self, point: Vec2, count: usize) -> Vec<usize> {
+        if count == 0 || self.is_empty() {
+            return Vec::new();
+        }
+        let (cx, cy) = self.cell(point);
+        let mut seen = HashSet::new();
+        let mut found: Vec<(f32, usize)> = Vec::new();
+        let mut probed = 0;
+        let mut ring = 0;
+        while seen.len() < self.items.len() {
+            if probed > self.cells.len() {
+                found = self
+                    .items
+                    .iter()
+                    .map(|(id, aabb)| (aabb.distance_squared(point), *id))
+                    .collect();
+                found.sort_by(|a, b| a.0.total_cmp(&b.0));
+                found.truncate(count);
+                break;
+            }
+            let cells = ring_cells(cx, cy, ring);
+            probed += cells.len();
+            for cell in cells {
+                for &id in self.cells.get(&cell).into_iter().flatten() {
+                    if seen.insert(id) {
+                        found.push((self.items[&id].distance_squared(point), id));
+                    }
+                }
+            }
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(count);
+            // Anything in the next ring is at least this far away.
+            let reach = ring as f32 * self.cell_size;
+            if found.len() == count
+                && found
+                    .last()
+                    .is_some_and(|(distance, _)| *distance <= reach * reach)
+            {
+                break;
+            }
+            ring += 1;
+        }
+        found.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Outlines of the cells in use.
+    pub fn empty(&self) -> Vec<Vec2> {
+        self.cells
+            .keys()
+            .flat_map(|(x, y)| {
+                let min = Vec2::new(*x as f32, *y as f32) * self.cell_size;
+                Aabb {
+                    min,
+                    max: min + Vec2::new(self.cell_size, self.cell_size),
+                }
+                .outline()
+            })
+            .collect()
+    }
+
+    fn insert_aabb(&mut self, id: usize, aabb: Aabb) {
+        self.remove(id);
+        for cell in self.cells_of(&aabb) {
+            self.cells.entry(cell).or_default().push(id);
+        }
+        self.items.insert(id, aabb);
+    }
+
+    fn cell(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn cells_of(&self, aabb: &Aabb) -> impl Iterator<Item = (i32, i32)> {
+        let ((left, top), (right, bottom)) = (self.cell(aabb.min), self.cell(aabb.max));
+        (top..=bottom).flat_map(move |y| (left..=right).map(move |x| (x, y)))
+    }
+
+    /// Items in the cells the area touches, each once.
+    fn candidates(&self, area: &Aabb) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        self.cells_of(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|id| seen.insert(*id))
+            .collect()
+    }
+}
+
+/// Cells exactly `ring` steps away from the center cell, counting diagonal steps as one.
+fn ring_cells(cx: i32, cy: i32, ring: i32) -> Vec<(i32, i32)> {
+    if ring == 0 {
+        return vec![(cx, cy)];
+    }
+    let mut cells = Vec::with_capacity(8 * ring as usize);
+    for offset in -ring..=ring {
+        cells.push((cx + offset, cy - ring));
+        cells.push((cx + offset, cy + ring));
+    }
+    for offset in -ring + 1..ring {
+        cells.push((cx - ring, cy + offset));
+        cells.push((cx + ring, cy + offset));
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::random::Random;
+
+    fn scatter(seed: u64, count: usize, size: f32) -> Vec<Vec2> {
+        let random = Random::new(seed);
+        (0..count)
+            .map(|_| {
+                Vec2::new(
+                    random.random_range(0.0, size),
+                    random.random_range(0.0, size),
+                )
+            })
+            .collect()
+    }
+
+    fn rect(x: f32, y: f32, width: u32, height: u32) -> Rect {
+        Rect::new(Vec2::new(x, y), Size::new(width, height))
+    }
+
+    fn sorted(mut ids: Vec<usize>) -> Vec<usize> {
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Distances of the ids' points to `point`, in the order given.
+    fn distances(points: &[Vec2], ids: &[usize], point: Vec2) -> Vec<f32> {
+        ids.iter()
+            .map(|id| points[*id].distance_squared(point))
+            .collect()
+    }
+
+    fn brute_nearest(points: &[Vec2], point: Vec2, count: usize) -> Vec<f32> {
+        let mut all: Vec<f32> = points
+            .iter()
+            .map(|other| other.distance_squared(point))
+            .collect();
+        all.sort_by(f32::total_cmp);
+        all.truncate(count);
+        all
+    }
+
+    fn filled(points: &[Vec2]) -> (Quadtree, SpatialHash) {
+        let mut tree = Quadtree::new(&rect(0.0, 0.0, 100, 100)).capacity(4);
+        let mut hash = SpatialHash::new(7.0);
+        for (id, point) in points.iter().enumerate() {
+            tree.insert(id, *point);
+            hash.insert(id, *point);
+        }
+        (tree, hash)
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let points = scatter(3, 400, 100.0);
+        let (tree, hash) = filled(&points);
+        // Inside, on the edge and well away from the items.
+        let mut queries = scatter(4, 50, 100.0);
+        queries.extend([
+            Vec2::new(0.0, 0.0),
+            Vec2::new(-500.0, 40.0),
+            Vec2::new(2000.0, 3000.0),
+        ]);
+        for query in queries {
+            for count in [1, 5, 17] {
+                let expected = brute_nearest(&points, query, count);
+                assert_eq!(
+                    distances(&points, &tree.nearest(query, count), query),
+                    expected
+                );
+                assert_eq!(
+                    distances(&points, &hash.nearest(query, count), query),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_on_empty_or_with_few_items() {
+        let tree = Quadtree::new(&rect(0.0, 0.0, 10, 10));
+        let hash = SpatialHash::new(1.0);
+        assert!(tree.nearest(Vec2::new(1.0, 1.0), 3).is_empty());
+        assert!(hash.nearest(Vec2::new(1.0, 1.0), 3).is_empty());
+
+        let points = scatter(5, 6, 10.0);
+        let (tree, hash) = filled(&points);
+        assert!(tree.nearest(Vec2::new(1.0, 1.0), 0).is_empty());
+        assert!(hash.nearest(Vec2::new(1.0, 1.0), 0).is_empty());
+        // Asking for more than there are gives all of them.
+        assert_eq!(
+            sorted(tree.nearest(Vec2::new(1.0, 1.0), 10)),
+            (0..6).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sorted(hash.nearest(Vec2::new(-1e6, 1e6), 10)),
+            (0..6).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn radius_and_rect_queries_match_brute_force() {
+        let points = scatter(8, 300, 100.0);
+        let (tree, hash) = filled(&points);
+        for center in scatter(9, 40, 100.0) {
+            for radius in [0.0, 3.0, 12.5, 40.0] {
+                let expected: Vec<usize> = (0..points.len())
+                    .filter(|id| points[*id].distance(center) <= radius)
+                    .collect();
+                assert_eq!(sorted(tree.query_radius(center, radius)), expected);
+                assert_eq!(sorted(hash.query_radius(center, radius)), expected);
+            }
+            let area = rect(center.x, center.y, 15, 30);
+            let (min, max) = (area.origin, area.bottom_right());
+            let expected: Vec<usize> = (0..points.len())
+                .filter(|id| {
+                    let point = points[*id];
+                    (min.x..=max.x).contains(&point.x) && (min.y..=max.y).contains(&point.y)
+                })
+                .collect();
+            assert_eq!(sorted(tree.query_rect(&area)), expected);
+            assert_eq!(sorted(hash.query_rect(&area)), expected);
+        }
+    }
+
+    #[test]
+    fn rects_are_found_by_any_part() {
+        let mut tree = Quadtree::new(&rect(0.0, 0.0, 64, 64)).capacity(1);
+        let mut hash = SpatialHash::new(4.0);
+        let rects = [
+            rect(10.0, 10.0, 30, 2),
+            rect(50.0, 50.0, 4, 4),
+            rect(1.0, 40.0, 2, 20),
+        ];
+        for (id, item) in rects.iter().enumerate() {
+            tree.insert_rect(id, item);
+            hash.insert_rect(id, item);
+        }
+        for index in 0..3 {
+            hash.insert(10 + index, Vec2::new(60.0, 2.0 + index as f32));
+            tree.insert(10 + index, Vec2::new(60.0, 2.0 + index as f32));
+        }
+        // The far end of the long rectangle, and nothing in between.
+        assert_eq!(tree.query_radius(Vec2::new(41.0, 11.0), 1.5), vec![0]);
+        assert_eq!(hash.query_radius(Vec2::new(41.0, 11.0), 1.5), vec![0]);
+        assert!(tree.query_rect(&rect(20.0, 20.0, 10, 10)).is_empty());
+        assert!(hash.query_rect(&rect(20.0, 20.0, 10, 10)).is_empty());
+        assert_eq!(
+            sorted(tree.query_rect(&rect(0.0, 45.0, 60, 10))),
+            vec![1, 2]
+        );
+        assert_eq!(
+            sorted(hash.query_rect(&rect(0.0, 45.0, 60, 10))),
+            vec![1, 2]
+        );
+        // A point inside a rectangle is nearest to it.
+        assert_eq!(tree.nearest(Vec2::new(2.0, 55.0), 1), vec![2]);
+        assert_eq!(hash.nearest(Vec2::new(2.0, 55.0), 1), vec![2]);
+    }
+
+    #[test]
+    fn moving_and_removing() {
+        let points = scatter(12, 200, 100.0);
+        let (mut tree, mut hash) = filled(&points);
+        let mut moved = points.clone();
+        let random = Random::new(13);
+        for id in (0..points.len()).step_by(3) {
+            moved[id] = Vec2::new(
+                random.random_range(0.0, 100.0),
+                random.random_range(0.0, 100.0),
+            );
+            tree.move_to(id, moved[id]);
+            hash.move_to(id, moved[id]);
+        }
+        assert_eq!((tree.len(), hash.len()), (200, 200));
+        let center = Vec2::new(50.0, 50.0);
+        let expected: Vec<usize> = (0..moved.len())
+            .filter(|id| moved[*id].distance(center) <= 20.0)
+            .collect();
+        assert_eq!(sorted(tree.query_radius(center, 20.0)), expected);
+        assert_eq!(sorted(hash.query_radius(center, 20.0)), expected);
+
+        for id in 0..moved.len() {
+            assert!(tree.remove(id) && hash.remove(id));
+        }
+        assert!(!tree.remove(0) && !hash.remove(0));
+        assert!(tree.is_empty() && hash.is_empty());
+        assert!(tree.query_radius(center, 100.0).is_empty());
+        // Emptied nodes merge back into the root and no cells are left.
+        assert!(tree.root.children.is_none());
+        assert!(hash.cells.is_empty() && hash.empty().is_empty());
+    }
+
+    #[test]
+    fn items_outside_the_bounds_still_work() {
+        let mut tree = Quadtree::new(&rect(0.0, 0.0, 10, 10)).capacity(1);
+        tree.insert(0, Vec2::new(5.0, 5.0));
+        tree.insert(1, Vec2::new(-50.0, 5.0));
+        tree.insert(2, Vec2::new(2.0, 2.0));
+        assert_eq!(tree.query_radius(Vec2::new(-49.0, 5.0), 2.0), vec![1]);
+        assert_eq!(tree.nearest(Vec2::new(-100.0, 0.0), 3), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn splitting_stops_at_the_max_depth() {
+        let mut tree = Quadtree::new(&rect(0.0, 0.0, 16, 16))
+            .capacity(1)
+            .max_depth(2);
+        for id in 0..10 {
+            tree.insert(id, Vec2::new(1.0, 1.0));
+        }
+        let mut depth = 0;
+        let mut node = &tree.root;
+        while let Some(children) = &node.children {
+            node = &children[0];
+            depth += 1;
+        }
+        assert_eq!((depth, node.items.len()), (2, 10));
+        assert_eq!(tree.nearest(Vec2::new(0.0, 0.0), 10).len(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn zero_capacity_panics() {
+        let _ = Quadtree::new(&rect(0.0, 0.0, 10, 10)).capacity(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive size")]
+    fn zero_cell_size_panics() {
+        SpatialHash::new(0.0);
+    }
+}
@@ -1,8 +1,9 @@
-use simple_pixels::{rgb::RGBA8, Context};
+use simple_pixels::rgb::RGBA8;
 
 use crate::{
+    canvas::Canvas,
     geometry::{Rect, Size},
-    math::Vec2,
+    math::{Affine2, Vec2},
 };
 
 pub struct Sprite {
@@ -37,9 +38,9 @@ impl Sprite {
             pixels,
         }
     }
-    pub fn draw(&self, ctx: &mut Context) {
-        let screen_width = ctx.width();
-        let screen_height = ctx.height();
+    pub fn draw(&self, canvas: &mut Canvas) {
+        let screen_width = canvas.width();
+        let screen_height = canvas.height();
 
         let screen_size = Size::new(screen_width, screen_height);
         let screen_origin = Vec2::new(0.0, 0.0);
@@ -73,7 +74,7 @@ impl Sprite {
             }
         }
 
-        ctx.draw_pixels(
+        canvas.draw_pixels(
             (screen_origin.x as i32).max(self.origin.x as i32),
             (screen_origin.y as i32).max(self.origin.y as i32),
             visible_width.try_into().unwrap(),
@@ -81,4 +82,82 @@ impl Sprite {
             &visible_pixels,
         );
     }
+
+    /// Draws the sprite with `transform` applied to its position on the canvas,
+    /// fully transparent pixels are skipped.
+    /// To spin a sprite in place use `Affine2::from_rotation_around(center, angle)`.
+    pub fn draw_transformed(&self, canvas: &mut Canvas, transform: &Affine2) {
+        let Some(inverse) = transform.inverse() else {
+            return;
+        };
+        let sprite_rect = Rect {
+            origin: self.origin,
+            size: self.size,
+        };
+        let corners = <[Vec2; 4]>::from(sprite_rect.transform(transform));
+        let Rect { origin, size } = Rect::bounding(&corners);
+
+        let from_x = (origin.x as i32).max(0);
+        let to_x = (origin.x as i32 + size.width as i32).min(canvas.width() as i32 - 1);
+        let from_y = (origin.y as i32).max(0);
+        let to_y = (origin.y as i32 + size.height as i32).min(canvas.height() as i32 - 1);
+        for y in from_y..=to_y {
+            for x in from_x..=to_x {
+                // Sample at the pixel center, so a 90 degree rotation maps pixels exactly.
+                let screen = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let local = (inverse.transform_point(screen) - self.origin).floor();
+                if local.x < 0.0
+                    || local.y < 0.0
+                    || local.x >= self.size.width as f32
+                    || local.y >= self.size.height as f32
+                {
+                    continue;
+                }
+                let pixel =
+                    self.pixels[local.y as usize * self.size.width as usize + local.x as usize];
+                if pixel.a != 0 {
+                    canvas.draw_pixel(x, y, pixel);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::canvas::Background;
+
+    #[test]
+    fn quarter_turn_maps_pixels_exactly() {
+        let (red, blue) = (RGBA8::new(255, 0, 0, 255), RGBA8::new(0, 0, 255, 255));
+        let sprite = Sprite::new(Vec2::new(1.0, 1.0), Size::new(2, 1), vec![red, blue]);
+        let mut canvas = Canvas::new(4, 4, Background::Keep);
+        let transform = Affine2::from_rotation_around(Vec2::new(1.0, 1.0), FRAC_PI_2);
+        sprite.draw_transformed(&mut canvas, &transform);
+        let black = RGBA8::new(0, 0, 0, 255);
+        let drawn: Vec<(usize, RGBA8)> = canvas
+            .pixels()
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, pixel)| *pixel != black)
+            .collect();
+        // Column 0, rows 1 and 2: the sprite now hangs down the left of the pivot.
+        assert_eq!(drawn, [(4, red), (8, blue)]);
+    }
+
+    #[test]
+    fn singular_transform_draws_nothing() {
+        let sprite = Sprite::new(
+            Vec2::new(0.0, 0.0),
+            Size::new(1, 1),
+            vec![RGBA8::new(255, 255, 255, 255)],
+        );
+        let mut canvas = Canvas::new(2, 2, Background::Keep);
+        sprite.draw_transformed(&mut canvas, &Affine2::from_scale(Vec2::new(0.0, 0.0)));
+        assert!(canvas.pixels().iter().all(|pixel| pixel.r == 0));
+    }
 }
@@ -0,0 +1,407 @@
+//! Tweens: values animated over time, driven by `Clock::now()`.
+
+use simple_pixels::rgb::RGBA8;
+
+use crate::clock::Clock;
+use crate::easing::Easing;
+use crate::math::Vec2;
+
+/// Values that can be blended, `t` of 0 gives `self` and 1 gives `other`.
+pub trait Tweenable: Copy {
+    fn tween(self, other: Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    #[allow(clippy::suboptimal_flops)]
+    fn tween(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn tween(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Tweenable for RGBA8 {
+    fn tween(self, other: Self, t: f32) -> Self {
+        let channel = |from: u8, to: u8| {
+            f32::from(from)
+                .tween(f32::from(to), t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+        Self::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+}
+
+/// Anything that can be sampled at a point in time, combine them with `then` and `with`.
+pub trait Animation {
+    type Output;
+
+    /// Seconds until the animation settles, infinite if it repeats forever.
+    fn duration(&self) -> f32;
+
+    /// State `time` seconds after the animation started.
+    fn sample(&self, time: f32) -> Self::Output;
+
+    /// Plays `next` once this one is done.
+    fn then<B>(self, next: B) -> Chain<Self, B>
+    where
+        Self: Sized,
+        B: Animation<Output = Self::Output>,
+    {
+        Chain { first: self, next }
+    }
+
+    /// Plays `other` at the same time, the output is a pair.
+    fn with<B: Animation>(self, other: B) -> Parallel<Self, B>
+    where
+        Self: Sized,
+    {
+        Parallel { a: self, b: other }
+    }
+
+    /// Starts the animation at the current time of `clock`.
+    fn start(self, clock: &Clock) -> Playing<Self>
+    where
+        Self: Sized,
+    {
+        Playing {
+            animation: self,
+            start: clock.now(),
+        }
+    }
+}
+
+/// Moves from one value to another over `duration` seconds.
+#[derive(Debug, Copy, Clone)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+    /// Extra plays after the first one, `None` repeats forever.
+    repeats: Option<u32>,
+    yoyo: bool,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            delay: 0.0,
+            easing: Easing::Linear,
+            repeats: Some(0),
+            yoyo: false,
+        }
+    }
+
+    pub fn easing(self, easing: Easing) -> Self {
+        Self { easing, ..self }
+    }
+
+    /// Waits this many seconds, holding the start value, before moving.
+    pub fn delay(self, delay: f32) -> Self {
+        Self {
+            delay: delay.max(0.0),
+            ..self
+        }
+    }
+
+    /// Plays `times` more times after the first one.
+    pub fn repeat(self, times: u32) -> Self {
+        Self {
+            repeats: Some(times),
+            ..self
+        }
+    }
+
+    pub fn repeat_forever(self) -> Self {
+        Self {
+            repeats: None,
+            ..self
+        }
+    }
+
+    /// Every other repeat plays backwards.
+    pub fn yoyo(self, yoyo: bool) -> Self {
+        Self { yoyo, ..self }
+    }
+
+    /// Uneased progress of the current play, yoyo plays already reversed.
+    fn progress(&self, time: f32) -> f32 {
+        let time = time - self.delay;
+        if time <= 0.0 {
+            return 0.0;
+        }
+        if self.duration <= 0.0 {
+            return self.final_progress();
+        }
+        let plays = time / self.duration;
+        let play = plays.floor();
+        if self
+            .repeats
+            .is_some_and(|repeats| play >= repeats as f32 + 1.0)
+        {
+            return self.final_progress();
+        }
+        let progress = plays - play;
+        if self.yoyo && play as u32 % 2 == 1 {
+            1.0 - progress
+        } else {
+            progress
+        }
+    }
+
+    /// Yoyo tweens with an odd number of repeats end back at the start.
+    fn final_progress(&self) -> f32 {
+        match self.repeats {
+            Some(repeats) if self.yoyo && repeats % 2 == 1 => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl<T: Tweenable> Animation for Tween<T> {
+    type Output = T;
+
+    #[allow(clippy::suboptimal_flops)]
+    fn duration(&self) -> f32 {
+        self.repeats.map_or(f32::INFINITY, |repeats| {
+            self.delay + self.duration * (repeats + 1) as f32
+        })
+    }
+
+    fn sample(&self, time: f32) -> T {
+        let t = self.easing.apply(self.progress(time));
+        self.from.tween(self.to, t)
+    }
+}
+
+/// Two animations one after another, see `Animation::then`.
+#[derive(Debug, Copy, Clone)]
+pub struct Chain<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A, B> Animation for Chain<A, B>
+where
+    A: Animation,
+    B: Animation<Output = A::Output>,
+{
+    type Output = A::Output;
+
+    fn duration(&self) -> f32 {
+        self.first.duration() + self.next.duration()
+    }
+
+    fn sample(&self, time: f32) -> Self::Output {
+        let first_duration = self.first.duration();
+        if time < first_duration {
+            self.first.sample(time)
+        } else {
+            self.next.sample(time - first_duration)
+        }
+    }
+}
+
+/// Two animations at the same time, see `Animation::with`.
+#[derive(Debug, Copy, Clone)]
+pub struct Parallel<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A: Animation, B: Animation> Animation for Parallel<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn duration(&self) -> f32 {
+        self.a.duration().max(self.b.duration())
+    }
+
+    fn sample(&self, time: f32) -> Self::Output {
+        (self.a.sample(time), self.b.sample(time))
+    }
+}
+
+/// Any number of animations of the same kind one after another.
+#[derive(Debug, Clone)]
+pub struct Sequence<A> {
+    steps: Vec<A>,
+}
+
+impl<A: Animation> Sequence<A> {
+    pub fn new(steps: Vec<A>) -> Self {
+        assert!(!steps.is_empty(), "A sequence needs at least one step");
+        Self { steps }
+    }
+}
+
+impl<A: Animation> Animation for Sequence<A> {
+    type Output = A::Output;
+
+    fn duration(&self) -> f32 {
+        self.steps.iter().map(Animation::duration).sum()
+    }
+
+    fn sample(&self, time: f32) -> Self::Output {
+        let mut time = time;
+        let (last, steps) = self.steps.split_last().expect("sequences aren't empty");
+        for step in steps {
+            let duration = step.duration();
+            if time < duration {
+                return step.sample(time);
+            }
+            time -= duration;
+        }
+        last.sample(time)
+    }
+}
+
+/// Same kind of animations played together, the output has one value per animation.
+#[derive(Debug, Clone)]
+pub struct Group<A> {
+    members: Vec<A>,
+}
+
+impl<A: Animation> Group<A> {
+    pub fn new(members: Vec<A>) -> Self {
+        Self { members }
+    }
+}
+
+impl<A: Animation> Animation for Group<A> {
+    type Output = Vec<A::Output>;
+
+    fn duration(&self) -> f32 {
+        self.members
+            .iter()
+            .map(Animation::duration)
+            .fold(0.0, f32::max)
+    }
+
+    fn sample(&self, time: f32) -> Self::Output {
+        self.members
+            .iter()
+            .map(|member| member.sample(time))
+            .collect()
+    }
+}
+
+/// An animation with the time it was started at.
+#[derive(Debug, Clone)]
+pub struct Playing<A> {
+    animation: A,
+    start: f32,
+}
+
+impl<A: Animation> Playing<A> {
+    pub fn value(&self, clock: &Clock) -> A::Output {
+        self.animation.sample(clock.now() - self.start)
+    }
+
+    pub fn is_finished(&self, clock: &Clock) -> bool {
+        clock.now() - self.start >= self.animation.duration()
+    }
+
+    pub fn restart(&mut self, clock: &Clock) {
+        self.start = clock.now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn tween_moves_and_holds() {
+        let tween = Tween::new(10.0, 20.0, 2.0).delay(1.0);
+        assert!(close(tween.duration(), 3.0));
+        assert!(close(tween.sample(0.0), 10.0));
+        assert!(close(tween.sample(1.0), 10.0));
+        assert!(close(tween.sample(2.0), 15.0));
+        assert!(close(tween.sample(3.0), 20.0));
+        assert!(close(tween.sample(100.0), 20.0));
+    }
+
+    #[test]
+    fn easing_is_applied() {
+        let tween = Tween::new(0.0, 1.0, 1.0).easing(Easing::In(crate::easing::Curve::Quad));
+        assert!(close(tween.sample(0.5), 0.25));
+    }
+
+    #[test]
+    fn repeats_and_yoyo() {
+        let tween = Tween::new(0.0, 1.0, 1.0).repeat(1).yoyo(true);
+        assert!(close(tween.duration(), 2.0));
+        assert!(close(tween.sample(0.25), 0.25));
+        assert!(close(tween.sample(1.25), 0.75));
+        // One repeat backwards ends at the start.
+        assert!(close(tween.sample(5.0), 0.0));
+
+        let forever = Tween::new(0.0, 1.0, 1.0).repeat_forever();
+        assert!(forever.duration().is_infinite());
+        assert!(close(forever.sample(7.5), 0.5));
+    }
+
+    #[test]
+    fn zero_duration_jumps_to_the_end() {
+        let tween = Tween::new(0.0, 1.0, 0.0);
+        assert!(close(tween.sample(0.0), 0.0));
+        assert!(close(tween.sample(0.001), 1.0));
+    }
+
+    #[test]
+    fn colors_and_vectors_blend() {
+        let color = RGBA8::new(0, 100, 255, 255).tween(RGBA8::new(255, 0, 255, 0), 0.5);
+        assert_eq!(color, RGBA8::new(128, 50, 255, 128));
+        let point = Vec2::new(0.0, 2.0).tween(Vec2::new(4.0, 0.0), 0.25);
+        assert!(close(point.x, 1.0) && close(point.y, 1.5));
+    }
+
+    #[test]
+    fn combinators() {
+        let a = Tween::new(0.0, 1.0, 1.0);
+        let b = Tween::new(1.0, 3.0, 2.0);
+        let chain = a.then(b);
+        assert!(close(chain.duration(), 3.0));
+        assert!(close(chain.sample(0.5), 0.5));
+        assert!(close(chain.sample(2.0), 2.0));
+
+        let parallel = a.with(b);
+        assert!(close(parallel.duration(), 2.0));
+        let (x, y) = parallel.sample(1.0);
+        assert!(close(x, 1.0) && close(y, 2.0));
+
+        let sequence = Sequence::new(vec![a, b, a]);
+        assert!(close(sequence.duration(), 4.0));
+        assert!(close(sequence.sample(3.5), 0.5));
+        assert!(close(sequence.sample(10.0), 1.0));
+
+        let group = Group::new(vec![a, b]);
+        assert!(close(group.duration(), 2.0));
+        let values = group.sample(0.5);
+        assert!(close(values[0], 0.5) && close(values[1], 1.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one step")]
+    fn empty_sequence_panics() {
+        Sequence::<Tween<f32>>::new(Vec::new());
+    }
+}